@@ -7,9 +7,12 @@ use coinbase_v3::{accounts::Account, basic_oauth::OAuthCbClient, client::CbClien
 async fn main() {
     let (client_id, client_secret, redirect_url) = utils::get_env_variables();
     let oauth_cb_client = OAuthCbClient::new(&client_id, &client_secret, &redirect_url)
+        .unwrap()
         .add_scope("wallet:accounts:read")
+        .unwrap()
         .authorize_once()
-        .await;
+        .await
+        .unwrap();
 
     let cb_client = CbClient::new(&oauth_cb_client);
     run_list_get_accounts(&cb_client).await;
@@ -5,9 +5,12 @@ use coinbase_v3::{basic_oauth::OAuthCbClient, client::CbClient, utils};
 async fn main() {
     let (client_id, client_secret, redirect_url) = utils::get_env_variables();
     let oauth_cb_client = OAuthCbClient::new(&client_id, &client_secret, &redirect_url)
+        .unwrap()
         .add_scope("wallet:transactions:read")
+        .unwrap()
         .authorize_once()
-        .await;
+        .await
+        .unwrap();
 
     let cb_client = CbClient::new(&oauth_cb_client);
     run_get_transactions_summary(&cb_client).await;
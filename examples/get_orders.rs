@@ -1,17 +1,18 @@
 use futures::{pin_mut, stream::StreamExt};
 
-use coinbase_v3::{
-    basic_oauth::OAuthCbClient, client::CbClient, orders, products, utils, DateTime,
-};
+use coinbase_v3::{basic_oauth::OAuthCbClient, client::CbClient, orders, products, utils};
 
 #[allow(dead_code)]
 #[tokio::main]
 async fn main() {
     let (client_id, client_secret, redirect_url) = utils::get_env_variables();
     let oauth_cb_client = OAuthCbClient::new(&client_id, &client_secret, &redirect_url)
+        .unwrap()
         .add_scope("wallet:transactions:read") // NOT wallet:orders:read as CB's doc says.
+        .unwrap()
         .authorize_once()
-        .await;
+        .await
+        .unwrap();
 
     let cb_client = CbClient::new(&oauth_cb_client);
     run_list_orders(&cb_client).await;
@@ -21,33 +22,12 @@ async fn main() {
 }
 
 pub async fn run_list_orders(cb_client: &CbClient<'_>) {
-    let product_id: Option<String> = None;
-    let order_status: Option<Vec<orders::Status>> = None;
-    let limit: Option<i32> = Some(10);
-    let start_date: Option<DateTime> = None;
-    let end_date: Option<DateTime> = None;
-    let deprecated_user_native_currency: Option<String> = None;
-    let order_type: Option<orders::OrderType> = None;
-    let order_side: Option<orders::OrderSide> = Some(orders::OrderSide::Buy);
-    let cursor: Option<String> = None;
-    let product_type: Option<products::ProductType> = Some(products::ProductType::Spot);
-    let order_placement_source: Option<orders::OrderPlacementSource> = None;
-    let contract_expiry_type: Option<products::ContractExpiryType> = None;
+    let query = orders::OrdersQuery::new()
+        .limit(10)
+        .order_side(orders::OrderSide::Buy)
+        .product_type(products::ProductType::Spot);
 
-    let orders_stream = cb_client.list_orders(
-        product_id,
-        order_status,
-        limit,
-        start_date,
-        end_date,
-        deprecated_user_native_currency,
-        order_type,
-        order_side,
-        cursor,
-        product_type,
-        order_placement_source,
-        contract_expiry_type,
-    );
+    let orders_stream = cb_client.list_orders_with_query(query);
     pin_mut!(orders_stream);
 
     let mut orders = Vec::<orders::Order>::new();
@@ -67,8 +47,8 @@ pub async fn run_list_orders(cb_client: &CbClient<'_>) {
 }
 
 pub async fn run_list_fills(cb_client: &CbClient<'_>) {
-    let limit = Some(10);
-    let fills_stream = cb_client.list_fills(None, None, None, None, limit, None);
+    let query = orders::FillsQuery::new().limit(10);
+    let fills_stream = cb_client.list_fills_with_query(query);
     pin_mut!(fills_stream);
 
     let mut fills = Vec::<orders::Fill>::new();
@@ -1,4 +1,4 @@
-use chrono;
+use bigdecimal::BigDecimal;
 
 use coinbase_v3::{
     basic_oauth::OAuthCbClient,
@@ -14,9 +14,12 @@ use coinbase_v3::{
 async fn main() {
     let (client_id, client_secret, redirect_url) = utils::get_env_variables();
     let oauth_cb_client = OAuthCbClient::new(&client_id, &client_secret, &redirect_url)
+        .unwrap()
         .add_scope("wallet:user:read")
+        .unwrap()
         .authorize_once()
-        .await;
+        .await
+        .unwrap();
 
     let cb_client = CbClient::new(&oauth_cb_client);
 
@@ -64,6 +67,17 @@ pub async fn run_get_product_book(cb_client: &CbClient<'_>) {
         let product_book = cb_client.get_product_book(product_id, limit).await.unwrap();
         println!("{:#?}", product_book);
     }
+
+    {
+        let product_id = "BTC-USD";
+        let limit = Some(5);
+        let aggregation_price_increment = BigDecimal::from(10);
+        let product_book = cb_client
+            .get_product_book_aggregated(product_id, limit, aggregation_price_increment)
+            .await
+            .unwrap();
+        println!("{:#?}", product_book);
+    }
 }
 
 pub async fn run_list_products(cb_client: &CbClient<'_>) {
@@ -125,15 +139,8 @@ pub async fn run_get_product(cb_client: &CbClient<'_>) {
 
 pub async fn run_get_product_candles(cb_client: &CbClient<'_>) {
     let product_id = "OGN-BTC";
-    let naivedatetime_utc = chrono::naive::NaiveDate::from_ymd_opt(2023, 1, 12)
-        .unwrap()
-        .and_hms_opt(0, 0, 0)
-        .unwrap();
-    let start = chrono::DateTime::from_utc(naivedatetime_utc, chrono::offset::Utc);
-    let end = start
-        .clone()
-        .checked_add_days(chrono::naive::Days::new(2))
-        .unwrap();
+    let start = utils::datetime_from_ymd(2023, 1, 12).unwrap();
+    let end = start.checked_add_days(chrono::naive::Days::new(2)).unwrap();
     let candles = cb_client
         .get_product_candles(product_id, &start, &end, Granularity::OneDay)
         .await
@@ -5,11 +5,14 @@ use coinbase_v3::{basic_oauth::OAuthCbClient, client::CbClient, error::CbError,
 async fn main() {
     let (client_id, client_secret, redirect_url) = utils::get_env_variables();
     let oauth_cb_client = OAuthCbClient::new(&client_id, &client_secret, &redirect_url)
+        .unwrap()
         .add_scope("wallet:buys:create")
+        .unwrap()
         .authorize_once()
-        .await;
+        .await
+        .unwrap();
 
-    let cb_client = CbClient::new(&oauth_cb_client);
+    let cb_client = CbClient::new(&oauth_cb_client).allow_trading(true);
     // run_order_and_cancel(&cb_client).await;
 
     run_cancel_nonexistent_order(&cb_client).await;
@@ -50,9 +53,22 @@ pub async fn run_cancel_nonexistent_order(cb_client: &CbClient<'_>) {
     match response {
         Ok(cancel_order_response) => println!("{:#?}", cancel_order_response),
         Err(err) => match err {
-            CbError::Coinbase(e) => println!("Coinbase error: {:#?}", e),
-            CbError::Serde(e) => println!("Serde error: {:#?}", e),
+            CbError::Coinbase { error, context } => {
+                println!("Coinbase error from {}: {:#?}", context, error)
+            }
+            CbError::Serde { source, .. } => println!("Serde error: {:#?}", source),
             CbError::Http(e) => println!("Http error: {:#?}", e),
+            CbError::Timeout => println!("Request timed out"),
+            CbError::RateLimited { retry_after } => {
+                println!("Rate limited, retry after {:?}", retry_after)
+            }
+            CbError::Auth(e) => println!("Auth error: {}", e),
+            CbError::TradingDisabled => println!("Trading is disabled"),
+            CbError::NotSettled { order_id, status } => {
+                println!("Order {} is not settled yet: {}", order_id, status)
+            }
+            CbError::WebSocket(e) => println!("WebSocket error: {}", e),
+            CbError::Other(e) => println!("Other error: {:#?}", e),
         },
     }
 }
@@ -17,11 +17,16 @@ use get_products::{
 async fn main() {
     let (client_id, client_secret, redirect_url) = utils::get_env_variables();
     let oauth_cb_client = OAuthCbClient::new(&client_id, &client_secret, &redirect_url)
-        .add_scope("wallet:accounts:read")
-        .add_scope("wallet:transactions:read")
-        .add_scope("wallet:user:read")
+        .unwrap()
+        .add_scopes([
+            "wallet:accounts:read",
+            "wallet:transactions:read",
+            "wallet:user:read",
+        ])
+        .unwrap()
         .authorize_once()
-        .await;
+        .await
+        .unwrap();
 
     let cb_client = CbClient::new(&oauth_cb_client);
 
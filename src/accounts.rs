@@ -1,10 +1,12 @@
 //! Structures & Enums to store Coinbase's Accounts
 
 use bigdecimal::BigDecimal;
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 use serde_enum_str::{Deserialize_enum_str, Serialize_enum_str};
 use uuid::Uuid;
 
+use crate::fees::FeeTier;
+use crate::orders::Order;
 use crate::DateTime;
 
 /// Possible types for Coinbase's accounts.
@@ -15,10 +17,13 @@ pub enum AccountType {
     AccountTypeCrypto,
     AccountTypeFiat,
     AccountTypeVault,
+    /// Any value not known by this crate.
+    #[serde(other)]
+    Other(String),
 }
 
 /// Structure to deserialize Coinbase's accounts.
-#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
 pub struct Account {
     pub uuid: Uuid,
     pub name: String,
@@ -34,8 +39,15 @@ pub struct Account {
     pub hold: Balance,
 }
 
+impl Account {
+    /// Total balance of the account, available or on hold, in the account's currency.
+    pub fn total_balance(&self) -> BigDecimal {
+        &self.available_balance.value + &self.hold.value
+    }
+}
+
 /// Structure to deserialize balances stored in a Coinbase's account.
-#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
 pub struct Balance {
     /// Not store as an `f64` as number of decimals might be currency dependant and arbitrary
     pub value: BigDecimal,
@@ -44,8 +56,9 @@ pub struct Balance {
 
 /// Structure to deserialize CB's response to a request for multiple accounts.
 ///
-/// Calls to this [Client][`crate::client::CbClient`]'s API will not return this type. It will unpack the
-/// inner `accounts` and return it.
+/// Calls to this [Client][`crate::client::CbClient`]'s API will mostly not return this type. They will
+/// unpack the inner `accounts` and return it, except for
+/// [list_accounts_page()](`crate::client::CbClient::list_accounts_page`).
 ///
 /// `has_next` and `cursor` are used for pagination.
 #[derive(Deserialize, Debug)]
@@ -65,16 +78,31 @@ pub struct AccountResponse {
     pub account: Account,
 }
 
+/// Structure storing a snapshot of the state of the user's accounts at a given time.
+///
+/// Created by [export_snapshot()](`crate::client::CbClient::export_snapshot`). Each part is fetched
+/// independently: if one fails, it is left to `None` and the error is recorded in `errors`.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct AccountSnapshot {
+    /// Time at which the snapshot was started.
+    pub time: DateTime,
+    pub accounts: Option<Vec<Account>>,
+    pub open_orders: Option<Vec<Order>>,
+    pub fee_tier: Option<FeeTier>,
+    /// Errors met while fetching the snapshot.
+    pub errors: Vec<String>,
+}
+
 //=========== TESTS ===========================================================
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::assert_enum_round_trip;
     use bigdecimal::ToPrimitive;
+    use std::str::FromStr;
 
-    #[test]
-    fn test_account_deserialize() {
-        let input = r##"[
+    const ACCOUNTS: &str = r##"[
       {
         "uuid": "9dd482e4-d8ce-46f7-a261-281843bd2855",
         "name": "SOL Wallet",
@@ -90,7 +118,36 @@ mod tests {
       }
 ]"##;
 
+    #[test]
+    fn test_account_type_round_trip() {
+        assert_enum_round_trip(&[
+            (
+                AccountType::AccountTypeUnspecified,
+                "ACCOUNT_TYPE_UNSPECIFIED",
+            ),
+            (AccountType::AccountTypeCrypto, "ACCOUNT_TYPE_CRYPTO"),
+            (AccountType::AccountTypeFiat, "ACCOUNT_TYPE_FIAT"),
+            (AccountType::AccountTypeVault, "ACCOUNT_TYPE_VAULT"),
+            (
+                AccountType::Other("ACCOUNT_TYPE_PERP_FUTURES".to_string()),
+                "ACCOUNT_TYPE_PERP_FUTURES",
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_account_unknown_type_deserialize() {
+        let input = ACCOUNTS.replace("ACCOUNT_TYPE_CRYPTO", "ACCOUNT_TYPE_INTX");
         let accounts: Vec<Account> = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(
+            accounts[0].r#type,
+            AccountType::Other("ACCOUNT_TYPE_INTX".to_string())
+        );
+    }
+
+    #[test]
+    fn test_account_deserialize() {
+        let accounts: Vec<Account> = serde_json::from_slice(ACCOUNTS.as_bytes()).unwrap();
         assert_eq!(accounts.len(), 1);
         assert_eq!(
             accounts[0].uuid.to_string(),
@@ -98,6 +155,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_account_serde_round_trip() {
+        let accounts: Vec<Account> = serde_json::from_slice(ACCOUNTS.as_bytes()).unwrap();
+        let json = serde_json::to_string(&accounts).unwrap();
+        let result: Vec<Account> = serde_json::from_str(&json).unwrap();
+        assert_eq!(result, accounts);
+    }
+
+    #[test]
+    fn test_account_total_balance() {
+        let mut accounts: Vec<Account> = serde_json::from_slice(ACCOUNTS.as_bytes()).unwrap();
+        let account = &mut accounts[0];
+        assert_eq!(
+            account.total_balance(),
+            BigDecimal::from_str("70.313593992").unwrap()
+        );
+
+        account.hold.value = BigDecimal::from_str("0.5").unwrap();
+        assert_eq!(
+            account.total_balance(),
+            BigDecimal::from_str("70.813593992").unwrap()
+        );
+    }
+
     #[test]
     fn test_balance_deserialize() {
         let input = r##"{ "value": "70.313593992", "currency": "SOL" }"##;
@@ -132,4 +213,19 @@ mod tests {
             serde_json::to_string(&AccountType::AccountTypeCrypto).unwrap()
         );
     }
+
+    #[test]
+    fn test_account_snapshot_serde() {
+        let accounts: Vec<Account> = serde_json::from_slice(ACCOUNTS.as_bytes()).unwrap();
+        let snapshot = AccountSnapshot {
+            time: chrono::Utc::now(),
+            accounts: Some(accounts),
+            open_orders: None,
+            fee_tier: None,
+            errors: vec!["open orders: http error".to_string()],
+        };
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let result: AccountSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(result, snapshot);
+    }
 }
@@ -0,0 +1,195 @@
+//! Trait abstracting the brokerage calls of [`CbClient`]
+//!
+//! Code written against [`BrokerageApi`] rather than [`CbClient`] can be unit tested with a fake
+//! implementation, without any Coinbase account nor network access.
+
+use async_trait::async_trait;
+use futures::stream::TryStreamExt;
+use uuid::Uuid;
+
+use crate::accounts::Account;
+use crate::client::CbClient;
+use crate::error::CbError;
+use crate::orders::{
+    CancelOrderResponse, CreateOrderResponse, Fill, FillsQuery, Order, OrderToSend, OrdersQuery,
+};
+use crate::products::{Pricebook, Product};
+
+type Result<T> = std::result::Result<T, CbError>;
+
+/// Read and trading calls of the Advanced Trade API, implemented by [`CbClient`].
+///
+/// Paginated listings are drained and returned as a single `Vec`. The returned futures are
+/// `Send`, so code generic over the API can be spawned on a multi-threaded runtime.
+///
+/// ```no_run
+/// # use coinbase_v3::api::BrokerageApi;
+/// # use coinbase_v3::error::CbError;
+/// // Generic over the API, so it can be tested with a fake implementation.
+/// async fn best_bid_price<A: BrokerageApi>(api: &A, product_id: &str) -> Result<String, CbError> {
+///     let pricebooks = api.get_best_bid_ask(&Some(vec![product_id])).await?;
+///     Ok(pricebooks[0].bids[0].price.to_string())
+/// }
+/// ```
+#[async_trait]
+pub trait BrokerageApi {
+    /// See [`CbClient::get_account`].
+    async fn get_account(&self, account_uuid: Uuid) -> Result<Account>;
+
+    /// See [`CbClient::get_product`].
+    async fn get_product(&self, product_id: &str) -> Result<Product>;
+
+    /// See [`CbClient::get_best_bid_ask`].
+    async fn get_best_bid_ask(&self, product_ids: &Option<Vec<&str>>) -> Result<Vec<Pricebook>>;
+
+    /// See [`CbClient::get_order`].
+    async fn get_order(&self, order_id: &str) -> Result<Order>;
+
+    /// All the orders matching `query`, see [`CbClient::list_orders_with_query`].
+    async fn get_orders(&self, query: OrdersQuery) -> Result<Vec<Order>>;
+
+    /// See [`CbClient::list_all_fills`].
+    async fn get_fills(&self, query: FillsQuery) -> Result<Vec<Fill>>;
+
+    /// See [`CbClient::create_order`].
+    async fn create_order(&self, order: &OrderToSend) -> Result<CreateOrderResponse>;
+
+    /// See [`CbClient::cancel_order`].
+    async fn cancel_order(&self, order_ids: &[String]) -> Result<Vec<CancelOrderResponse>>;
+}
+
+#[async_trait]
+impl BrokerageApi for CbClient<'_> {
+    async fn get_account(&self, account_uuid: Uuid) -> Result<Account> {
+        CbClient::get_account(self, account_uuid).await
+    }
+
+    async fn get_product(&self, product_id: &str) -> Result<Product> {
+        CbClient::get_product(self, product_id).await
+    }
+
+    async fn get_best_bid_ask(&self, product_ids: &Option<Vec<&str>>) -> Result<Vec<Pricebook>> {
+        CbClient::get_best_bid_ask(self, product_ids).await
+    }
+
+    async fn get_order(&self, order_id: &str) -> Result<Order> {
+        CbClient::get_order(self, order_id).await
+    }
+
+    async fn get_orders(&self, query: OrdersQuery) -> Result<Vec<Order>> {
+        self.list_orders_with_query(query).try_concat().await
+    }
+
+    async fn get_fills(&self, query: FillsQuery) -> Result<Vec<Fill>> {
+        self.list_all_fills(query).await
+    }
+
+    async fn create_order(&self, order: &OrderToSend) -> Result<CreateOrderResponse> {
+        CbClient::create_order(self, order).await
+    }
+
+    async fn cancel_order(&self, order_ids: &[String]) -> Result<Vec<CancelOrderResponse>> {
+        CbClient::cancel_order(self, &order_ids.to_vec()).await
+    }
+}
+
+//=========== TESTS ===========================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_oauth::AccessTokenProvider;
+    use bigdecimal::BigDecimal;
+    use oauth2::AccessToken;
+    use std::str::FromStr;
+
+    /// Fake API serving a fixed book, as a downstream user would write one.
+    struct FakeApi;
+
+    #[async_trait]
+    impl BrokerageApi for FakeApi {
+        async fn get_account(&self, _account_uuid: Uuid) -> Result<Account> {
+            Err(CbError::Other(anyhow::anyhow!("not faked")))
+        }
+
+        async fn get_product(&self, _product_id: &str) -> Result<Product> {
+            Err(CbError::Other(anyhow::anyhow!("not faked")))
+        }
+
+        async fn get_best_bid_ask(
+            &self,
+            product_ids: &Option<Vec<&str>>,
+        ) -> Result<Vec<Pricebook>> {
+            let product_ids = product_ids.clone().unwrap_or_default();
+            Ok(product_ids
+                .iter()
+                .map(|product_id| {
+                    serde_json::from_value(serde_json::json!({
+                        "product_id": product_id,
+                        "bids": [{"price": "99", "size": "1"}],
+                        "asks": [{"price": "101", "size": "1"}],
+                        "time": "2023-06-01T10:00:00Z"
+                    }))
+                    .unwrap()
+                })
+                .collect())
+        }
+
+        async fn get_order(&self, _order_id: &str) -> Result<Order> {
+            Err(CbError::Other(anyhow::anyhow!("not faked")))
+        }
+
+        async fn get_orders(&self, _query: OrdersQuery) -> Result<Vec<Order>> {
+            Ok(vec![])
+        }
+
+        async fn get_fills(&self, _query: FillsQuery) -> Result<Vec<Fill>> {
+            Ok(vec![])
+        }
+
+        async fn create_order(&self, _order: &OrderToSend) -> Result<CreateOrderResponse> {
+            Err(CbError::TradingDisabled)
+        }
+
+        async fn cancel_order(&self, _order_ids: &[String]) -> Result<Vec<CancelOrderResponse>> {
+            Err(CbError::TradingDisabled)
+        }
+    }
+
+    /// Strategy code under test, generic over the API.
+    async fn spread<A: BrokerageApi>(api: &A, product_id: &str) -> Result<BigDecimal> {
+        let pricebooks = api.get_best_bid_ask(&Some(vec![product_id])).await?;
+        pricebooks[0]
+            .spread()
+            .ok_or(CbError::Other(anyhow::anyhow!("empty book")))
+    }
+
+    fn assert_send<T: Send>(_: T) {}
+
+    struct DummyTokenProvider;
+
+    impl AccessTokenProvider for DummyTokenProvider {
+        fn access_token(&self) -> Result<AccessToken> {
+            Ok(AccessToken::new("dummy".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_futures_are_send() {
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider);
+        assert_send(BrokerageApi::get_order(&cb_client, "order-1"));
+        assert_send(spread(&FakeApi, "BTC-USD"));
+    }
+
+    #[tokio::test]
+    async fn test_fake_api() {
+        let result = spread(&FakeApi, "BTC-USD").await.unwrap();
+        assert_eq!(result, BigDecimal::from_str("2").unwrap());
+        assert!(FakeApi
+            .get_orders(OrdersQuery::new())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,17 +1,21 @@
 //! OAuth2 related functionalities
 
 use std::collections::HashSet;
+use std::future::Future;
 use std::io::{BufRead, BufReader, Write};
 use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use oauth2::reqwest::async_http_client;
 use oauth2::{
     basic::BasicClient, revocation::StandardRevocableToken, AccessToken, AuthUrl,
-    AuthorizationCode, ClientId, ClientSecret, CsrfToken, RedirectUrl, RefreshToken, RevocationUrl,
-    Scope, TokenResponse, TokenUrl,
+    AuthorizationCode, ClientId, ClientSecret, CsrfToken, HttpRequest, HttpResponse,
+    PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, RefreshToken, RevocationUrl, Scope,
+    TokenResponse, TokenUrl,
 };
 use url::Url;
 
+use crate::error::CbError;
 use crate::scopes::VALID_SCOPES;
 
 const AUTH_URL_STR: &str = "https://www.coinbase.com/oauth/authorize";
@@ -24,32 +28,86 @@ const REVOKE_URL_STR: &str = "https://api.coinbase.com/oauth/revoke";
 /// ```no_run
 /// # use coinbase_v3::basic_oauth;
 /// # use coinbase_v3::client;
-/// # let client_with_access_token_provider_trait = basic_oauth::OAuthCbClient::new("", "", "");
+/// # let client_with_access_token_provider_trait = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
 /// let cb_client = client::CbClient::new(&client_with_access_token_provider_trait);
 /// ```
 /// the `oauth_cb_client` should implement this trait.
-pub trait AccessTokenProvider {
-    /// Should return a valid [`oauth2::AccessToken()`](https://docs.rs/oauth2/latest/oauth2/struct.AccessToken.html).
-    fn access_token(&self) -> AccessToken;
+///
+/// Providers must be `Sync`, as the [`CbClient`](`crate::client::CbClient`) borrowing them is shared
+/// by the futures of its requests, which are `Send` and can be spawned on a multi-threaded runtime.
+pub trait AccessTokenProvider: Sync {
+    /// Should return a valid [`oauth2::AccessToken()`](https://docs.rs/oauth2/latest/oauth2/struct.AccessToken.html),
+    /// or a [`CbError::Auth`] error if none is available.
+    fn access_token(&self) -> Result<AccessToken, CbError>;
+
+    /// Should return a valid access token for the request `method url`.
+    ///
+    /// Defaults to [`AccessTokenProvider::access_token()`]. Providers minting a token per
+    /// request, like [`crate::jwt_auth::JwtTokenProvider`], override it.
+    fn request_access_token(&self, _method: &str, _url: &str) -> Result<AccessToken, CbError> {
+        self.access_token()
+    }
 }
 
 /// Returning the access token stored by the OAuthCbClient.
 ///
 /// Note that the token might be expired and invalid.
+/// Returns a [`CbError::Auth`] error if the client was not authorized yet.
 impl AccessTokenProvider for OAuthCbClient {
-    fn access_token(&self) -> AccessToken {
-        self.access_token.clone().unwrap()
+    fn access_token(&self) -> Result<AccessToken, CbError> {
+        self.access_token.clone().ok_or(CbError::Auth(
+            "no access token, the client should be authorized first".to_string(),
+        ))
     }
 }
 
-fn set_oauth_cb_urls() -> (AuthUrl, TokenUrl, RevocationUrl) {
-    let auth_url =
-        AuthUrl::new(AUTH_URL_STR.to_string()).expect("Invalid authorization endpoint URL");
-    let token_url = TokenUrl::new(TOKEN_URL_STR.to_string()).expect("Invalid token endpoint URL");
-    let revoke_url =
-        RevocationUrl::new(REVOKE_URL_STR.to_string()).expect("Invalid revocation endpoint URL");
+fn set_oauth_cb_urls() -> Result<(AuthUrl, TokenUrl, RevocationUrl), CbError> {
+    let auth_url = AuthUrl::new(AUTH_URL_STR.to_string())
+        .map_err(|err| invalid_url_error("authorization endpoint", AUTH_URL_STR, err))?;
+    let token_url = TokenUrl::new(TOKEN_URL_STR.to_string())
+        .map_err(|err| invalid_url_error("token endpoint", TOKEN_URL_STR, err))?;
+    let revoke_url = RevocationUrl::new(REVOKE_URL_STR.to_string())
+        .map_err(|err| invalid_url_error("revocation endpoint", REVOKE_URL_STR, err))?;
+
+    Ok((auth_url, token_url, revoke_url))
+}
+
+/// Extract the authorization code and the CSRF state from the first line of the redirection
+/// request, e.g. `GET /?code=...&state=... HTTP/1.1`.
+fn parse_redirect_request(
+    request_line: &str,
+    scheme: &str,
+    host: &str,
+) -> Result<(AuthorizationCode, CsrfToken), CbError> {
+    let redirect_url = request_line
+        .split_whitespace()
+        .nth(1)
+        .ok_or_else(|| CbError::Auth(format!("invalid redirection request '{}'", request_line)))?;
+    let url = Url::parse(&(scheme.to_string() + "://" + host + redirect_url))
+        .map_err(|err| invalid_url_error("redirection", redirect_url, err))?;
+
+    let query_value = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+            .ok_or_else(|| CbError::Auth(format!("no {} in the redirection", name)))
+    };
+
+    Ok((
+        AuthorizationCode::new(query_value("code")?),
+        CsrfToken::new(query_value("state")?),
+    ))
+}
+
+fn io_error(err: std::io::Error) -> CbError {
+    CbError::Auth(format!(
+        "failed to receive the authorization redirection: {}",
+        err
+    ))
+}
 
-    (auth_url, token_url, revoke_url)
+fn invalid_url_error(name: &str, url: &str, err: url::ParseError) -> CbError {
+    CbError::Auth(format!("invalid {} URL '{}': {}", name, url, err))
 }
 
 /// A simple client to manage OAuth2 access tokens and permissions
@@ -58,6 +116,10 @@ pub struct OAuthCbClient {
     access_token: Option<AccessToken>,
     refresh_token: Option<RefreshToken>,
     scopes: HashSet<Scope>,
+    // Send a PKCE code challenge with the authorization request.
+    pkce: bool,
+    // Set once the token has been revoked, to only warn on drop about live tokens.
+    revoked: AtomicBool,
 }
 
 impl OAuthCbClient {
@@ -68,31 +130,35 @@ impl OAuthCbClient {
     /// let client_id = "my_secret_client_id_provided_by_coinbase";
     /// let client_secret = "my_client_secret_provided_by_coinbase";
     /// let redirect_url = "http://localhost:3001";
-    /// let oauth_cb_client = OAuthCbClient::new(client_id, client_secret, redirect_url);
+    /// let oauth_cb_client = OAuthCbClient::new(client_id, client_secret, redirect_url).unwrap();
     /// ```
     ///
     /// - `client_id` and `client secret` are given to you by the API service provider. Store them
-    /// in a safe place. For instance hardcodding them in the source code is a bad idea.
+    ///   in a safe place. For instance hardcodding them in the source code is a bad idea.
     /// - `redirect_url` is the url you will be asked to access to authenticate. Make sure it is
-    /// accessible to you.
-    pub fn new(client_id: &str, client_secret: &str, redirect_url: &str) -> Self {
+    ///   accessible to you.
+    ///
+    /// Returns a [`CbError::Auth`] error if `redirect_url` is not a valid URL.
+    pub fn new(client_id: &str, client_secret: &str, redirect_url: &str) -> Result<Self, CbError> {
         let client_id = ClientId::new(client_id.to_string());
         let client_secret = ClientSecret::new(client_secret.to_string());
-        let redirect_url =
-            RedirectUrl::new(redirect_url.to_string()).expect("Invalid redirect_url");
+        let redirect_url = RedirectUrl::new(redirect_url.to_string())
+            .map_err(|err| invalid_url_error("redirect", redirect_url, err))?;
 
-        let (auth_url, token_url, revoke_url) = set_oauth_cb_urls();
+        let (auth_url, token_url, revoke_url) = set_oauth_cb_urls()?;
 
         let client = BasicClient::new(client_id, Some(client_secret), auth_url, Some(token_url))
             .set_redirect_uri(redirect_url)
             .set_revocation_uri(revoke_url);
 
-        Self {
+        Ok(Self {
             client,
             access_token: None,
             refresh_token: None,
             scopes: HashSet::new(),
-        }
+            pkce: false,
+            revoked: AtomicBool::new(false),
+        })
     }
 
     /// AccessToken are only valid for predifnied scopes.
@@ -100,144 +166,372 @@ impl OAuthCbClient {
     /// To add one scope, for instance
     /// ```no_run
     /// # use coinbase_v3::basic_oauth::OAuthCbClient;
-    /// # let oauth_cb_client = OAuthCbClient::new("", "", "");
-    /// oauth_cb_client.add_scope("wallet:transactions:read");
+    /// # let oauth_cb_client = OAuthCbClient::new("", "", "").unwrap();
+    /// oauth_cb_client.add_scope("wallet:transactions:read").unwrap();
     /// ```
-    /// It can be called multiple times to add mutliple scopes.
+    /// It can be called multiple times to add mutliple scopes, see also
+    /// [add_scopes()](`OAuthCbClient::add_scopes`).
     /// Refer to Coinbase's documentation for adding the appropriate scopes.
     /// As it can be confusing, you may refer to the examples of the current package
     /// to find out which ones are needed.
-    pub fn add_scope(mut self, scope_description: &str) -> Self {
-        assert!(VALID_SCOPES.contains(&scope_description));
+    ///
+    /// Returns a [`CbError::Auth`] error if the scope is not a valid Coinbase scope.
+    pub fn add_scope(mut self, scope_description: &str) -> Result<Self, CbError> {
+        if !VALID_SCOPES.contains(&scope_description) {
+            return Err(CbError::Auth(format!(
+                "invalid scope '{}'",
+                scope_description
+            )));
+        }
 
         self.scopes
             .insert(Scope::new(scope_description.to_string()));
 
+        Ok(self)
+    }
+
+    /// Add several scopes at once.
+    ///
+    /// ```no_run
+    /// # use coinbase_v3::basic_oauth::OAuthCbClient;
+    /// # let oauth_cb_client = OAuthCbClient::new("", "", "").unwrap();
+    /// oauth_cb_client
+    ///     .add_scopes(["wallet:accounts:read", "wallet:transactions:read"])
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Returns a [`CbError::Auth`] error on the first scope that is not a valid Coinbase scope.
+    pub fn add_scopes<'s, I>(self, scope_descriptions: I) -> Result<Self, CbError>
+    where
+        I: IntoIterator<Item = &'s str>,
+    {
+        scope_descriptions
+            .into_iter()
+            .try_fold(self, |client, scope_description| {
+                client.add_scope(scope_description)
+            })
+    }
+
+    /// Use PKCE (Proof Key for Code Exchange) during [authorize_once()](`OAuthCbClient::authorize_once`).
+    ///
+    /// A random code verifier is generated for each authorization, only its SHA-256 challenge
+    /// is sent with the authorization request. The verifier is then required to exchange the
+    /// code for a token, so an intercepted code is useless on its own.
+    ///
+    /// Coinbase accepts PKCE with the authorization code flow. It is recommended for public
+    /// clients, like mobile or desktop applications, which cannot keep their client secret
+    /// confidential, and can be used by confidential (server side) clients too.
+    ///
+    /// ```no_run
+    /// # use coinbase_v3::basic_oauth::OAuthCbClient;
+    /// # let oauth_cb_client = OAuthCbClient::new("", "", "").unwrap();
+    /// let oauth_cb_client = oauth_cb_client.with_pkce();
+    /// ```
+    pub fn with_pkce(mut self) -> Self {
+        self.pkce = true;
         self
     }
 
-    /// Get Tokens from the issuing authority. Returns once it has stored them. Otherwise crashes.
+    /// Build the url the user is asked to open, along with the CSRF state and the PKCE code
+    /// verifier, if enabled, to check and use once redirected.
+    fn authorize_url(&self) -> (Url, CsrfToken, Option<PkceCodeVerifier>) {
+        let mut request = self
+            .client
+            .authorize_url(CsrfToken::new_random)
+            .add_scopes(self.scopes.clone());
+        let mut pkce_verifier = None;
+        if self.pkce {
+            let (pkce_challenge, verifier) = PkceCodeChallenge::new_random_sha256();
+            request = request.set_pkce_challenge(pkce_challenge);
+            pkce_verifier = Some(verifier);
+        }
+        let (url, csrf_state) = request.url();
+
+        (url, csrf_state, pkce_verifier)
+    }
+
+    /// Get Tokens from the issuing authority. Returns once it has stored them.
     ///
     /// ```no_run
     /// # use coinbase_v3::basic_oauth::OAuthCbClient;
     /// # use tokio_test;
     /// # tokio_test::block_on(async {
-    /// # let oauth_cb_client = OAuthCbClient::new("", "", "");
-    /// oauth_cb_client.add_scope("wallet:transactions:read")
-    ///             .authorize_once().await;
+    /// # let oauth_cb_client = OAuthCbClient::new("", "", "").unwrap();
+    /// oauth_cb_client.add_scope("wallet:transactions:read").unwrap()
+    ///             .authorize_once().await.unwrap();
     /// # });
     /// ```
     ///
     /// *Once*, because it does not instantiate a mechanism to renew tokens.
     /// So after 2 hours, the tokens will be invalid.
-    pub async fn authorize_once(mut self: Self) -> Self {
-        let redirect_url = self.client.redirect_url().unwrap();
+    ///
+    /// Returns a [`CbError::Auth`] error if the redirection can not be received, if its CSRF
+    /// state does not match the one sent (which may indicate an attack), or if the code can not
+    /// be exchanged for a token.
+    pub async fn authorize_once(mut self) -> Result<Self, CbError> {
+        let redirect_url = self
+            .client
+            .redirect_url()
+            .ok_or_else(|| CbError::Auth("no redirect_url set".to_string()))?;
         let scheme = redirect_url.url().scheme().to_string();
-        let host = redirect_url.url().host().unwrap().to_string();
-        let port = redirect_url.url().port().unwrap();
+        let host = redirect_url
+            .url()
+            .host_str()
+            .ok_or_else(|| CbError::Auth("redirect_url has no host".to_string()))?
+            .to_string();
+        let port = redirect_url
+            .url()
+            .port_or_known_default()
+            .ok_or_else(|| CbError::Auth("redirect_url has no port".to_string()))?;
 
         let listener_address = host.to_string() + ":" + &port.to_string();
 
-        let (authorize_url, csrf_state) = self
-            .client
-            .authorize_url(CsrfToken::new_random)
-            .add_scopes(self.scopes.clone())
-            .url();
+        let (authorize_url, csrf_state, pkce_verifier) = self.authorize_url();
+
+        println!("\nOpen this URL in your browser:\n{}\n\n", authorize_url);
+
+        let listener = TcpListener::bind(listener_address).map_err(io_error)?;
+        let (mut stream, _) = listener.accept().map_err(io_error)?;
 
-        println!(
-            "\nOpen this URL in your browser:\n{}\n\n",
-            authorize_url.to_string()
+        let mut request_line = String::new();
+        BufReader::new(&stream)
+            .read_line(&mut request_line)
+            .map_err(io_error)?;
+        let (code, state) = parse_redirect_request(&request_line, &scheme, &host)?;
+
+        let message = "Go back to your terminal :)";
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{}",
+            message.len(),
+            message
         );
+        stream.write_all(response.as_bytes()).map_err(io_error)?;
+        if state.secret() != csrf_state.secret() {
+            return Err(CbError::Auth(
+                "CSRF state mismatch in the authorization redirection".to_string(),
+            ));
+        }
 
-        let listener = TcpListener::bind(listener_address).unwrap();
-        for stream in listener.incoming() {
-            if let Ok(mut stream) = stream {
-                let code;
-                let state;
-                {
-                    let mut reader = BufReader::new(&stream);
-
-                    let mut request_line = String::new();
-                    reader.read_line(&mut request_line).unwrap();
-
-                    let redirect_url = request_line.split_whitespace().nth(1).unwrap();
-                    let url = Url::parse(&(scheme + "://" + &host + redirect_url)).unwrap();
-
-                    let code_pair = url
-                        .query_pairs()
-                        .find(|pair| {
-                            let &(ref key, _) = pair;
-                            key == "code"
-                        })
-                        .unwrap();
-
-                    let (_, value) = code_pair;
-                    code = AuthorizationCode::new(value.into_owned());
-
-                    let state_pair = url
-                        .query_pairs()
-                        .find(|pair| {
-                            let &(ref key, _) = pair;
-                            key == "state"
-                        })
-                        .unwrap();
-
-                    let (_, value) = state_pair;
-                    state = CsrfToken::new(value.into_owned());
-                }
-
-                let message = "Go back to your terminal :)";
-                let response = format!(
-                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{}",
-                    message.len(),
-                    message
-                );
-                stream.write_all(response.as_bytes()).unwrap();
-                assert!(state.secret() == csrf_state.secret());
-
-                // Exchange the code with a token.
-                let token_response = self
-                    .client
-                    .exchange_code(code)
-                    .request_async(async_http_client)
-                    .await;
-
-                let token_response = token_response.unwrap();
-                if let Some(tok) = token_response.refresh_token() {
-                    self.refresh_token = Some(tok.clone());
-                }
-                self.access_token = Some(token_response.access_token().clone());
+        // Exchange the code with a token.
+        let mut token_request = self.client.exchange_code(code);
+        if let Some(verifier) = pkce_verifier {
+            token_request = token_request.set_pkce_verifier(verifier);
+        }
+        let token_response = token_request
+            .request_async(async_http_client)
+            .await
+            .map_err(|err| CbError::Auth(format!("token exchange failed: {}", err)))?;
 
-                break;
-            }
+        if let Some(tok) = token_response.refresh_token() {
+            self.refresh_token = Some(tok.clone());
         }
-        self
+        self.access_token = Some(token_response.access_token().clone());
+
+        Ok(self)
     }
 
     /// Revoke the obtained token
     ///
     /// Just to make sure no one can use it afterwards.
     /// Note that without calling this function, Coinbase tokens normally expire after 2 hours.
+    ///
+    /// Prefer [close()](`OAuthCbClient::close`), which returns an error instead of panicking.
     pub async fn revoke_access(&self) {
-        let token_to_revoke: StandardRevocableToken = match self.refresh_token.as_ref() {
-            Some(token) => token.into(),
-            None => self.access_token.as_ref().unwrap().into(),
-        };
+        self.revoke(async_http_client)
+            .await
+            .expect("Failed to revoke token");
+
+        println!("=============== ACCESS REVOKED =================");
+    }
+
+    /// Revoke the obtained token and consume the client.
+    ///
+    /// ```no_run
+    /// # use coinbase_v3::basic_oauth::OAuthCbClient;
+    /// # use tokio_test;
+    /// # tokio_test::block_on(async {
+    /// # let oauth_cb_client = OAuthCbClient::new("", "", "").unwrap();
+    /// oauth_cb_client.close().await.unwrap();
+    /// # });
+    /// ```
+    ///
+    /// Tokens can not be revoked on drop, as dropping can not wait for a request. This is the
+    /// preferred way to end a session: with the `tracing` feature, dropping a client holding a
+    /// token that was never revoked logs a warning.
+    ///
+    /// Returns a [`CbError::Auth`] error if the revocation request fails.
+    pub async fn close(self) -> Result<(), CbError> {
+        self.close_with(async_http_client).await
+    }
+
+    async fn close_with<C, F, RE>(self, http_client: C) -> Result<(), CbError>
+    where
+        C: FnOnce(HttpRequest) -> F,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        RE: std::error::Error + 'static,
+    {
+        self.revoke(http_client).await
+    }
+
+    /// Revoke the refresh token if any, the access token otherwise. Nothing to do without token.
+    async fn revoke<C, F, RE>(&self, http_client: C) -> Result<(), CbError>
+    where
+        C: FnOnce(HttpRequest) -> F,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        RE: std::error::Error + 'static,
+    {
+        let token_to_revoke: StandardRevocableToken =
+            match (self.refresh_token.as_ref(), self.access_token.as_ref()) {
+                (Some(token), _) => token.into(),
+                (None, Some(token)) => token.into(),
+                (None, None) => return Ok(()),
+            };
 
         self.client
             .revoke_token(token_to_revoke)
-            .unwrap()
-            .request_async(async_http_client)
+            .map_err(|err| CbError::Auth(format!("token revocation failed: {}", err)))?
+            .request_async(http_client)
             .await
-            .expect("Failed to revoke token");
+            .map_err(|err| CbError::Auth(format!("token revocation failed: {}", err)))?;
+        self.revoked.store(true, Ordering::Relaxed);
 
-        println!("=============== ACCESS REVOKED =================");
+        Ok(())
     }
 }
 
-// impl Drop for OauthCbClient {
-//     fn drop(&mut self) {
-//         tokio::spawn(self.revoke_access());
-//         println!("oauth cb client dropped.");
-//     }
-// }
+/// Warn about tokens left valid, see [close()](`OAuthCbClient::close`).
+#[cfg(feature = "tracing")]
+impl Drop for OAuthCbClient {
+    fn drop(&mut self) {
+        let has_token = self.access_token.is_some() || self.refresh_token.is_some();
+        if has_token && !self.revoked.load(Ordering::Relaxed) {
+            tracing::warn!("OAuthCbClient dropped without revoking its token, call close() first");
+        }
+    }
+}
+
+//=========== TESTS ===========================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_authorize_once_csrf_mismatch() {
+        // Find a free port, released right away for the client to listen on.
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let redirect_url = format!("http://127.0.0.1:{}", port);
+        let oauth_cb_client = OAuthCbClient::new("id", "secret", &redirect_url)
+            .unwrap()
+            .add_scope("wallet:user:read")
+            .unwrap();
+
+        let browser = std::thread::spawn(move || loop {
+            if let Ok(mut stream) = std::net::TcpStream::connect(("127.0.0.1", port)) {
+                stream
+                    .write_all(b"GET /?code=abc&state=forged HTTP/1.1\r\n\r\n")
+                    .unwrap();
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        });
+
+        let result = oauth_cb_client.authorize_once().await;
+        browser.join().unwrap();
+        match result {
+            Err(CbError::Auth(message)) => assert!(message.contains("CSRF")),
+            _ => panic!("a CSRF mismatch should be an error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_redirect_request() {
+        let (code, state) =
+            parse_redirect_request("GET /?code=abc&state=xyz HTTP/1.1", "http", "localhost")
+                .unwrap();
+        assert_eq!(code.secret(), "abc");
+        assert_eq!(state.secret(), "xyz");
+
+        let result = parse_redirect_request("GET /?state=xyz HTTP/1.1", "http", "localhost");
+        assert!(matches!(result, Err(CbError::Auth(_))));
+    }
+
+    #[test]
+    fn test_authorize_url_pkce() {
+        let oauth_cb_client = OAuthCbClient::new("id", "secret", "http://localhost:3001").unwrap();
+        let (url, _, pkce_verifier) = oauth_cb_client.authorize_url();
+        assert!(pkce_verifier.is_none());
+        assert!(!url.query_pairs().any(|(key, _)| key == "code_challenge"));
+
+        let oauth_cb_client = oauth_cb_client.with_pkce();
+        let (url, _, pkce_verifier) = oauth_cb_client.authorize_url();
+        let pkce_verifier = pkce_verifier.unwrap();
+        let challenge = PkceCodeChallenge::from_code_verifier_sha256(&pkce_verifier);
+        let query: HashSet<(String, String)> = url.query_pairs().into_owned().collect();
+        assert!(query.contains(&("code_challenge_method".to_string(), "S256".to_string())));
+        assert!(query.contains(&("code_challenge".to_string(), challenge.as_str().to_string())));
+    }
+
+    #[test]
+    fn test_invalid_redirect_url() {
+        let result = OAuthCbClient::new("id", "secret", "not a url");
+        assert!(matches!(result, Err(CbError::Auth(_))));
+    }
+
+    #[test]
+    fn test_add_scopes() {
+        let oauth_cb_client = OAuthCbClient::new("id", "secret", "http://localhost:3001")
+            .unwrap()
+            .add_scopes(["wallet:accounts:read", "wallet:transactions:read"])
+            .unwrap()
+            .add_scope("wallet:user:read")
+            .unwrap();
+        assert_eq!(oauth_cb_client.scopes.len(), 3);
+        assert!(oauth_cb_client
+            .scopes
+            .contains(&Scope::new("wallet:transactions:read".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_close_revokes_token() {
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/oauth/revoke"))
+            .and(body_string_contains("token=my_refresh_token"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut oauth_cb_client =
+            OAuthCbClient::new("id", "secret", "http://localhost:3001").unwrap();
+        oauth_cb_client.access_token = Some(AccessToken::new("my_access_token".to_string()));
+        oauth_cb_client.refresh_token = Some(RefreshToken::new("my_refresh_token".to_string()));
+
+        // oauth2 only accepts https revocation URLs, redirect the request to the mock server.
+        let server_uri = server.uri();
+        let redirect_to_server = |mut request: HttpRequest| {
+            request.url = Url::parse(&(server_uri + request.url.path())).unwrap();
+            async_http_client(request)
+        };
+        oauth_cb_client
+            .close_with(redirect_to_server)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_add_invalid_scope() {
+        let result = OAuthCbClient::new("id", "secret", "http://localhost:3001")
+            .unwrap()
+            .add_scopes(["wallet:accounts:read", "wallet:acounts:write"]);
+        assert!(matches!(result, Err(CbError::Auth(_))));
+    }
+}
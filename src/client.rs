@@ -1,37 +1,75 @@
 //! Client with all the calls to Coinbase Advanced API
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
 
 use async_stream::try_stream;
-use futures::stream::Stream;
+use bigdecimal::BigDecimal;
+use futures::pin_mut;
+use futures::stream::{Stream, StreamExt, TryStreamExt};
+use rand::Rng;
 use reqwest;
 use uritemplate::UriTemplate;
 use uuid::Uuid;
 
-use crate::accounts::{Account, AccountResponse, AccountsResponse};
+use crate::accounts::{Account, AccountResponse, AccountSnapshot, AccountsResponse};
 use crate::basic_oauth::AccessTokenProvider;
-use crate::error::{CbError, CbRequestError};
-use crate::fees;
+use crate::convert::{self, ConvertTrade, ConvertTradeRequest, ConvertTradeResponse};
+use crate::error::{CbError, CbRequestError, RequestContext};
+use crate::fees::{self, TransactionsSummaryQuery};
+use crate::futures::{
+    create_close_position, FuturesBalanceSummary, FuturesBalanceSummaryResponse, FuturesPosition,
+    FuturesPositionResponse, FuturesPositionsResponse,
+};
 use crate::orders::{
-    CancelOrderResponse, CancelOrdersResponse, CreateOrderResponse, FillsResponse, Order,
-    OrdersResponse,
+    CancelOrderFailureReason, CancelOrderResponse, CancelOrdersResponse, CreateOrderResponse,
+    EditOrderResponse, FillsQuery, FillsResponse, Order, OrdersQuery, OrdersResponse,
+    PreviewOrderResponse,
+};
+use crate::portfolios::{
+    self, MoveFundsResponse, Portfolio, PortfolioBreakdown, PortfolioBreakdownResponse,
+    PortfolioResponse, PortfolioType, PortfoliosResponse,
 };
 use crate::products::{
-    Candle, CandlesResponse, ContractExpiryType, Granularity, MarketTrades, Pricebook,
+    self, Candle, CandlesResponse, ContractExpiryType, Granularity, MarketTrades, Pricebook,
     PricebookResponse, PricebooksResponse, Product, ProductType, ProductsResponse,
 };
+use crate::time::ServerTime;
 use crate::MAIN_URL;
 use crate::{orders, DateTime};
 
 /// Client structure performing http requests to Coinbase Advanced API
+///
+/// Clones share the same connection pool and rate limiter.
+#[derive(Clone)]
 pub struct CbClient<'a> {
     https_client: reqwest::Client,
+    // Set when the http client was given by the user, whose settings must not be discarded.
+    custom_http_client: bool,
     // It is the responsability of the token provider to give a valid one.
     access_token_provider: &'a (dyn AccessTokenProvider + 'a),
+    // Requests placing, editing or cancelling orders are refused unless explicitly allowed.
+    trading_allowed: bool,
+    // Retry policy for transient errors. No retries by default.
+    max_retries: u32,
+    retry_base_delay: Duration,
+    retry_posts: bool,
+    // Defaults to MAIN_URL, can point to a sandbox or a mock server instead.
+    base_url: String,
+    // Sent with every request, so Coinbase can identify the client.
+    user_agent: String,
+    // Applied to every request on top of the reqwest client's own settings. None by default.
+    timeout: Option<Duration>,
+    // Shared by all clones of the client. No rate limit by default.
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 type Result<T> = std::result::Result<T, CbError>;
 
+/// User-Agent sent by default, e.g. `coinbase-v3/0.1.1`.
+const DEFAULT_USER_AGENT: &str = concat!("coinbase-v3/", env!("CARGO_PKG_VERSION"));
+
 impl<'a> CbClient<'a> {
     /// Instantiate a new client.
     ///
@@ -46,29 +84,231 @@ impl<'a> CbClient<'a> {
     /// # use coinbase_v3::utils;
     /// # let (client_id, client_secret, redirect_url) = utils::get_env_variables();
     /// // Create / get a provider implementing the [AccessTokenProvider](`basic_oauth::AccessTokenProvider`) trait.
-    /// let oauth_cb_client = basic_oauth::OAuthCbClient::new(&client_id, &client_secret, &redirect_url);
+    /// let oauth_cb_client = basic_oauth::OAuthCbClient::new(&client_id, &client_secret, &redirect_url).unwrap();
     /// // Instantiate the client
     /// let cb_client = client::CbClient::new(&oauth_cb_client);
     /// ```
     pub fn new(oauth_cb_client: &'a (dyn AccessTokenProvider + 'a)) -> Self {
         CbClient {
-            https_client: reqwest::Client::new(),
+            custom_http_client: false,
+            ..Self::with_http_client(oauth_cb_client, reqwest::Client::new())
+        }
+    }
+
+    /// Instantiate a new client sending its requests with a pre-built `http_client`.
+    ///
+    /// Useful to set timeouts, connection pools, proxies or a custom TLS configuration.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use coinbase_v3::basic_oauth;
+    /// # use coinbase_v3::client;
+    /// # let oauth_cb_client = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
+    /// let http_client = reqwest::Client::builder()
+    ///     .timeout(Duration::from_secs(10))
+    ///     .build()
+    ///     .unwrap();
+    /// let cb_client = client::CbClient::with_http_client(&oauth_cb_client, http_client);
+    /// ```
+    pub fn with_http_client(
+        oauth_cb_client: &'a (dyn AccessTokenProvider + 'a),
+        http_client: reqwest::Client,
+    ) -> Self {
+        CbClient {
+            https_client: http_client,
+            custom_http_client: true,
             access_token_provider: oauth_cb_client,
+            trading_allowed: false,
+            max_retries: 0,
+            retry_base_delay: Duration::ZERO,
+            retry_posts: false,
+            base_url: MAIN_URL.to_string(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            timeout: None,
+            rate_limiter: None,
+        }
+    }
+
+    /// Send the requests to `url` instead of [`MAIN_URL`].
+    ///
+    /// Useful to target Coinbase's sandbox or a local mock server.
+    ///
+    /// ```no_run
+    /// # use coinbase_v3::basic_oauth;
+    /// # use coinbase_v3::client;
+    /// # let oauth_cb_client = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
+    /// let cb_client = client::CbClient::new(&oauth_cb_client)
+    ///     .with_base_url("https://api-sandbox.coinbase.com/api/v3");
+    /// ```
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Allow (or not) the client to place, edit and cancel orders.
+    ///
+    /// Trading is disabled by default: [create_order()](`crate::client::CbClient::create_order`),
+    /// [edit_order()](`crate::client::CbClient::edit_order`) and
+    /// [cancel_order()](`crate::client::CbClient::cancel_order`) return a
+    /// [`CbError::TradingDisabled`] error until it is explicitly enabled. Read-only requests are
+    /// not affected.
+    ///
+    /// ```no_run
+    /// # use coinbase_v3::basic_oauth;
+    /// # use coinbase_v3::client;
+    /// # let oauth_cb_client = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
+    /// let cb_client = client::CbClient::new(&oauth_cb_client).allow_trading(true);
+    /// ```
+    pub fn allow_trading(mut self, allowed: bool) -> Self {
+        self.trading_allowed = allowed;
+        self
+    }
+
+    fn ensure_trading_allowed(&self) -> Result<()> {
+        if self.trading_allowed {
+            Ok(())
+        } else {
+            Err(CbError::TradingDisabled)
+        }
+    }
+
+    /// Retry requests failing with a transient error, with exponential backoff and jitter.
+    ///
+    /// Connection errors, timeouts and 5xx server errors (500, 502, 503, 504) are retried up to
+    /// `max_retries` times. The n-th retry waits between half and all of `base_delay * 2^n`.
+    ///
+    /// Only idempotent GET, PUT and DELETE requests are retried: POST requests, like creating an
+    /// order, could be executed twice. See [retry_posts()](`crate::client::CbClient::retry_posts`) to retry them too.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use coinbase_v3::basic_oauth;
+    /// # use coinbase_v3::client;
+    /// # let oauth_cb_client = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
+    /// let cb_client =
+    ///     client::CbClient::new(&oauth_cb_client).with_retry(3, Duration::from_millis(200));
+    /// ```
+    pub fn with_retry(mut self, max_retries: u32, base_delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_base_delay = base_delay;
+        self
+    }
+
+    /// Fail requests, including reading the response, taking longer than `timeout` with
+    /// [`CbError::Timeout`]. Requests have no timeout by default.
+    ///
+    /// The timeout applies to each attempt when retries are enabled with
+    /// [with_retry()](`crate::client::CbClient::with_retry`).
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use coinbase_v3::basic_oauth;
+    /// # use coinbase_v3::client;
+    /// # let oauth_cb_client = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
+    /// let cb_client = client::CbClient::new(&oauth_cb_client).with_timeout(Duration::from_secs(10));
+    /// ```
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Throttle requests to at most `requests_per_second`, with bursts of up to
+    /// `requests_per_second` requests, instead of being rate limited by Coinbase.
+    ///
+    /// Requests over the limit wait for their turn. The limit is shared by all clones of the
+    /// client, and retries count as requests.
+    ///
+    /// ```no_run
+    /// # use coinbase_v3::basic_oauth;
+    /// # use coinbase_v3::client;
+    /// # let oauth_cb_client = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
+    /// let cb_client = client::CbClient::new(&oauth_cb_client).with_rate_limit(10);
+    /// ```
+    pub fn with_rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(requests_per_second)));
+        self
+    }
+
+    /// Replace the http client by one keeping at most `max_idle_per_host` idle connections open
+    /// to Coinbase, and sending TCP keep-alive probes every `tcp_keepalive` if given.
+    ///
+    /// By default, reqwest keeps any number of idle connections for 90 seconds, without TCP
+    /// keep-alive. Latency-sensitive clients sending a steady flow of requests are better off
+    /// with a few connections kept alive, e.g. `with_connection_pool(4, Some(Duration::from_secs(30)))`,
+    /// saving a TCP and TLS handshake on requests following a quiet period.
+    ///
+    /// Only applies to the default http client: a client given to
+    /// [with_http_client()](`crate::client::CbClient::with_http_client`) is never replaced, build
+    /// it with `pool_max_idle_per_host()` and `tcp_keepalive()` instead.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use coinbase_v3::basic_oauth;
+    /// # use coinbase_v3::client;
+    /// # let oauth_cb_client = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
+    /// let cb_client = client::CbClient::new(&oauth_cb_client)
+    ///     .with_connection_pool(4, Some(Duration::from_secs(30)))
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Returns a [`CbError::Other`] error if the client was built with a custom http client, and
+    /// a [`CbError::Http`] error if the http client can not be built.
+    pub fn with_connection_pool(
+        mut self,
+        max_idle_per_host: usize,
+        tcp_keepalive: Option<Duration>,
+    ) -> Result<Self> {
+        if self.custom_http_client {
+            return Err(CbError::Other(anyhow::anyhow!(
+                "can not set the connection pool of a custom http client"
+            )));
         }
+        self.https_client = reqwest::Client::builder()
+            .pool_max_idle_per_host(max_idle_per_host)
+            .tcp_keepalive(tcp_keepalive)
+            .build()?;
+        Ok(self)
+    }
+
+    /// Send `user_agent` as the `User-Agent` header instead of the default `coinbase-v3/<version>`.
+    ///
+    /// ```no_run
+    /// # use coinbase_v3::basic_oauth;
+    /// # use coinbase_v3::client;
+    /// # let oauth_cb_client = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
+    /// let cb_client = client::CbClient::new(&oauth_cb_client).with_user_agent("my-bot/1.0");
+    /// ```
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Also retry POST requests on transient errors, according to the policy set with
+    /// [with_retry()](`crate::client::CbClient::with_retry`).
+    ///
+    /// !Warning! A request failing on the way back may have been executed by Coinbase: retrying
+    /// it can for instance place the same order twice.
+    pub fn retry_posts(mut self, allowed: bool) -> Self {
+        self.retry_posts = allowed;
+        self
     }
 
     async fn get<T>(&self, request_url: &str) -> Result<T>
     where
         T: serde::de::DeserializeOwned,
     {
-        let response = self
-            .https_client
-            .get(request_url)
-            .bearer_auth(self.access_token_provider.access_token().secret())
-            .send()
-            .await?;
-
-        Self::unpack_response(response).await
+        self.execute("GET", request_url, self.max_retries, || {
+            Ok(self
+                .https_client
+                .get(request_url)
+                .header(reqwest::header::USER_AGENT, &self.user_agent)
+                .bearer_auth(
+                    self.access_token_provider
+                        .request_access_token("GET", request_url)?
+                        .secret(),
+                ))
+        })
+        .await
     }
 
     async fn post<T, U>(&self, request_url: &str, object: &T) -> Result<U>
@@ -76,29 +316,178 @@ impl<'a> CbClient<'a> {
         T: serde::ser::Serialize,
         U: serde::de::DeserializeOwned,
     {
-        let response = self
-            .https_client
-            .post(request_url)
-            .json(object)
-            .bearer_auth(self.access_token_provider.access_token().secret())
-            .send()
-            .await?;
+        let max_retries = if self.retry_posts {
+            self.max_retries
+        } else {
+            0
+        };
+        self.execute("POST", request_url, max_retries, || {
+            Ok(self
+                .https_client
+                .post(request_url)
+                .header(reqwest::header::USER_AGENT, &self.user_agent)
+                .json(object)
+                .bearer_auth(
+                    self.access_token_provider
+                        .request_access_token("POST", request_url)?
+                        .secret(),
+                ))
+        })
+        .await
+    }
+
+    async fn put<T, U>(&self, request_url: &str, object: &T) -> Result<U>
+    where
+        T: serde::ser::Serialize,
+        U: serde::de::DeserializeOwned,
+    {
+        self.execute("PUT", request_url, self.max_retries, || {
+            Ok(self
+                .https_client
+                .put(request_url)
+                .header(reqwest::header::USER_AGENT, &self.user_agent)
+                .json(object)
+                .bearer_auth(
+                    self.access_token_provider
+                        .request_access_token("PUT", request_url)?
+                        .secret(),
+                ))
+        })
+        .await
+    }
+
+    async fn delete<U>(&self, request_url: &str) -> Result<U>
+    where
+        U: serde::de::DeserializeOwned,
+    {
+        self.execute("DELETE", request_url, self.max_retries, || {
+            Ok(self
+                .https_client
+                .delete(request_url)
+                .header(reqwest::header::USER_AGENT, &self.user_agent)
+                .bearer_auth(
+                    self.access_token_provider
+                        .request_access_token("DELETE", request_url)?
+                        .secret(),
+                ))
+        })
+        .await
+    }
+
+    /// Send the request built by `build_request` and parse its response.
+    ///
+    /// With the `tracing` feature, the call is wrapped in a span recording the method, the
+    /// endpoint path, the status code and the elapsed time. Neither the token nor the query are
+    /// recorded.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    async fn execute<F, U>(
+        &self,
+        method: &'static str,
+        request_url: &str,
+        max_retries: u32,
+        build_request: F,
+    ) -> Result<U>
+    where
+        F: Fn() -> Result<reqwest::RequestBuilder>,
+        U: serde::de::DeserializeOwned,
+    {
+        #[cfg(feature = "tracing")]
+        {
+            use tracing::Instrument;
+
+            let span = tracing::debug_span!(
+                "coinbase_request",
+                method,
+                path = %request_path(request_url),
+                status = tracing::field::Empty,
+                elapsed_ms = tracing::field::Empty,
+            );
+            let start = std::time::Instant::now();
+            let result = async {
+                let response = self.send_with_retry(max_retries, build_request).await?;
+                tracing::Span::current().record("status", response.status().as_u16());
+                Self::unpack_response(method, response).await
+            }
+            .instrument(span.clone())
+            .await;
+            span.record("elapsed_ms", start.elapsed().as_millis() as u64);
+            if let Err(err) = &result {
+                span.in_scope(|| tracing::warn!(error = %err, "Coinbase request failed"));
+            }
+            result
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        {
+            let response = self.send_with_retry(max_retries, build_request).await?;
+            Self::unpack_response(method, response).await
+        }
+    }
 
-        Self::unpack_response(response).await
+    /// Send the request built by `build_request`, rebuilding and sending it again on transient
+    /// errors, up to `max_retries` times.
+    ///
+    /// Once retries are exhausted, the last response or error is returned as is.
+    async fn send_with_retry<F>(
+        &self,
+        max_retries: u32,
+        build_request: F,
+    ) -> Result<reqwest::Response>
+    where
+        F: Fn() -> Result<reqwest::RequestBuilder>,
+    {
+        let mut attempt = 0;
+        loop {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+            let mut request = build_request()?;
+            if let Some(timeout) = self.timeout {
+                request = request.timeout(timeout);
+            }
+            let result = request.send().await;
+            let is_transient = match &result {
+                Ok(response) => is_transient_status(response.status()),
+                Err(err) => err.is_connect() || err.is_timeout(),
+            };
+            if !is_transient || attempt >= max_retries {
+                return Ok(result?);
+            }
+            tokio::time::sleep(backoff_delay(self.retry_base_delay, attempt)).await;
+            attempt += 1;
+        }
     }
 
-    async fn unpack_response<T>(response: reqwest::Response) -> Result<T>
+    /// Parse the body of `response` to the `method` request, as a `T` or as a Coinbase error.
+    ///
+    /// Parsing errors and Coinbase errors hold the method, path and status of the request.
+    async fn unpack_response<T>(method: &str, response: reqwest::Response) -> Result<T>
     where
         T: serde::de::DeserializeOwned,
     {
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(CbError::RateLimited {
+                retry_after: parse_retry_after(response.headers()),
+            });
+        }
+
+        let context = Box::new(RequestContext {
+            method: method.to_string(),
+            path: response.url().path().to_string(),
+            status: response.status().as_u16(),
+        });
         let text_content = response.text().await?;
-        println!("{:#?}", text_content);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(body = %text_content, "Coinbase response");
 
         match serde_json::from_str::<T>(&text_content) {
             Ok(result) => Ok(result),
             Err(err) => match serde_json::from_str::<CbRequestError>(&text_content) {
-                Ok(cb_err) => Err(CbError::Coinbase(cb_err)),
-                Err(_) => Err(CbError::Serde(err)),
+                Ok(error) => Err(CbError::Coinbase { error, context }),
+                Err(_) => Err(CbError::Serde {
+                    source: err,
+                    context: Some(context),
+                }),
             },
         }
     }
@@ -115,37 +504,69 @@ impl<'a> CbClient<'a> {
         cursor: Option<String>,
     ) -> impl Stream<Item = Result<Vec<Account>>> + 'b {
         try_stream! {
-            let uri = Self::get_list_accounts_uri(limit, cursor);
-            let mut accounts_response: AccountsResponse = self.get(&uri).await?;
+            let mut accounts_response = self.list_accounts_page(limit, cursor).await?;
             yield accounts_response.accounts;
 
             while accounts_response.has_next {
                 let cursor = Some(accounts_response.cursor.clone());
-                let uri = Self::get_list_accounts_uri(limit, cursor);
-                accounts_response= self.get(&uri).await?;
+                accounts_response = self.list_accounts_page(limit, cursor).await?;
                 yield accounts_response.accounts;
             }
         }
     }
 
-    fn get_list_accounts_uri(limit: Option<i32>, cursor: Option<String>) -> String {
+    /// List a single batch of accounts, keeping the pagination information.
+    ///
+    /// `limit` elements, starting from `cursor`. Contrary to
+    /// [list_accounts()](`crate::client::CbClient::list_accounts`), the returned
+    /// [`AccountsResponse`] exposes `has_next` and `cursor`, so the pagination can be driven
+    /// manually, for instance to resume it later from a stored cursor.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getaccounts)
+    pub async fn list_accounts_page(
+        &self,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> Result<AccountsResponse> {
+        let uri = self.get_list_accounts_uri(limit, cursor);
+        self.get(&uri).await
+    }
+
+    fn get_list_accounts_uri(&self, limit: Option<i32>, cursor: Option<String>) -> String {
         let args = QueryArgs::new()
             .add_optional_scalar_arg("limit", &limit)
             .add_optional_scalar_arg("cursor", &cursor);
-        let uri_string = MAIN_URL.to_string() + "/brokerage/accounts{?query*}";
+        let uri_string = self.base_url.clone() + "/brokerage/accounts{?query*}";
         let uri = UriTemplate::new(&uri_string)
             .set("query", args.get())
             .build();
         uri
     }
 
+    /// Get the account holding `currency`, e.g. "btc" or "BTC", if any.
+    ///
+    /// Coinbase cannot filter accounts by currency, so accounts are listed until one matches.
+    pub async fn get_account_by_currency(&self, currency: &str) -> Result<Option<Account>> {
+        let accounts_stream = self.list_accounts(None, None);
+        pin_mut!(accounts_stream);
+        while let Some(accounts) = accounts_stream.try_next().await? {
+            if let Some(account) = accounts
+                .into_iter()
+                .find(|account| account.currency.eq_ignore_ascii_case(currency))
+            {
+                return Ok(Some(account));
+            }
+        }
+        Ok(None)
+    }
+
     /// Get a Single Account by id.
     ///
     /// A list of valid ids can be retrieve using [list_accounts()](`crate::client::CbClient::list_accounts`)
     ///
     /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getaccount)
     pub async fn get_account(&self, account_uuid: Uuid) -> Result<Account> {
-        let uri_string = MAIN_URL.to_string() + "/brokerage/accounts/{uuid}";
+        let uri_string = self.base_url.clone() + "/brokerage/accounts/{uuid}";
         let uri = UriTemplate::new(&uri_string)
             .set("uuid", account_uuid.to_string())
             .build();
@@ -153,6 +574,69 @@ impl<'a> CbClient<'a> {
         Ok(account_response.account)
     }
 
+    /// Same as [`CbClient::get_account`], with the account id given as a string.
+    ///
+    /// Fails with [`CbError::Other`] without sending any request if `account_uuid` is not a valid
+    /// UUID.
+    pub async fn get_account_by_uuid_str(&self, account_uuid: &str) -> Result<Account> {
+        let uuid = Uuid::parse_str(account_uuid).map_err(|err| {
+            CbError::Other(anyhow::anyhow!(
+                "Invalid account uuid {:?}: {}",
+                account_uuid,
+                err
+            ))
+        })?;
+        self.get_account(uuid).await
+    }
+
+    /// Take a snapshot of all accounts, open orders and the current fee tier.
+    ///
+    /// Each part is fetched independently: a failure is recorded in the snapshot's `errors` and
+    /// the matching field is left to `None`, instead of failing the whole snapshot.
+    pub async fn export_snapshot(&self) -> Result<AccountSnapshot> {
+        let time = chrono::Utc::now();
+        let mut errors = Vec::new();
+
+        let accounts = match self.list_accounts(None, None).try_concat().await {
+            Ok(accounts) => Some(accounts),
+            Err(err) => {
+                errors.push(format!("accounts: {}", err));
+                None
+            }
+        };
+
+        let open_orders = match self
+            .list_orders_with_query(OrdersQuery::new().order_status(vec![orders::Status::Open]))
+            .try_concat()
+            .await
+        {
+            Ok(orders) => Some(orders),
+            Err(err) => {
+                errors.push(format!("open orders: {}", err));
+                None
+            }
+        };
+
+        let fee_tier = match self
+            .get_transactions_summary(None, None, None, None, None)
+            .await
+        {
+            Ok(transactions_summary) => Some(transactions_summary.fee_tier),
+            Err(err) => {
+                errors.push(format!("fee tier: {}", err));
+                None
+            }
+        };
+
+        Ok(AccountSnapshot {
+            time,
+            accounts,
+            open_orders,
+            fee_tier,
+            errors,
+        })
+    }
+
     /// Get the best bid/ask for all products. A subset of all products can be returned instead by using the product_ids input.
     ///
     /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getbestbidask)
@@ -161,7 +645,7 @@ impl<'a> CbClient<'a> {
         product_ids: &Option<Vec<&str>>,
     ) -> Result<Vec<Pricebook>> {
         let args = QueryArgs::new().add_optional_vec_args("product_ids", product_ids);
-        let uri_string = MAIN_URL.to_string() + "/brokerage/best_bid_ask{?query*}";
+        let uri_string = self.base_url.clone() + "/brokerage/best_bid_ask{?query*}";
         let uri = UriTemplate::new(&uri_string)
             .set("query", args.get())
             .build();
@@ -169,6 +653,18 @@ impl<'a> CbClient<'a> {
         Ok(pricebooks_response.pricebooks)
     }
 
+    /// Same as [`CbClient::get_best_bid_ask`], with the pricebooks keyed by product_id.
+    pub async fn get_best_bid_ask_map(
+        &self,
+        product_ids: &Option<Vec<&str>>,
+    ) -> Result<HashMap<String, Pricebook>> {
+        let pricebooks = self.get_best_bid_ask(product_ids).await?;
+        Ok(pricebooks
+            .into_iter()
+            .map(|pricebook| (pricebook.product_id.clone(), pricebook))
+            .collect())
+    }
+
     /// Get a list of bids/asks for a single product. The amount of detail shown can be customized with the limit parameter.
     ///
     /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getproductbook)
@@ -177,18 +673,44 @@ impl<'a> CbClient<'a> {
         product_id: &str,
         limit: Option<i32>,
     ) -> Result<Pricebook> {
-        let args = QueryArgs::new()
-            .add_mandatory_arg("product_id", &product_id)
-            .add_optional_scalar_arg("limit", &limit);
-        let uri_string = MAIN_URL.to_string() + "/brokerage/product_book/{?query*}";
-        let uri = UriTemplate::new(&uri_string)
-            .set("query", args.get())
-            .build();
+        let uri = self.get_product_book_uri(product_id, limit, None);
+        let pricebook_response: PricebookResponse = self.get(&uri).await?;
+
+        Ok(pricebook_response.pricebook)
+    }
+
+    /// Same as [`CbClient::get_product_book`], with the levels coalesced into buckets of
+    /// `aggregation_price_increment`, for a coarser book.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getproductbook)
+    pub async fn get_product_book_aggregated(
+        &self,
+        product_id: &str,
+        limit: Option<i32>,
+        aggregation_price_increment: BigDecimal,
+    ) -> Result<Pricebook> {
+        let uri = self.get_product_book_uri(product_id, limit, Some(aggregation_price_increment));
         let pricebook_response: PricebookResponse = self.get(&uri).await?;
 
         Ok(pricebook_response.pricebook)
     }
 
+    fn get_product_book_uri(
+        &self,
+        product_id: &str,
+        limit: Option<i32>,
+        aggregation_price_increment: Option<BigDecimal>,
+    ) -> String {
+        let args = QueryArgs::new()
+            .add_mandatory_arg("product_id", &product_id)
+            .add_optional_scalar_arg("limit", &limit)
+            .add_optional_scalar_arg("aggregation_price_increment", &aggregation_price_increment);
+        let uri_string = self.base_url.clone() + "/brokerage/product_book/{?query*}";
+        UriTemplate::new(&uri_string)
+            .set("query", args.get())
+            .build()
+    }
+
     /// Get a list of the available currency pairs for trading.
     ///
     /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getproducts)
@@ -200,35 +722,154 @@ impl<'a> CbClient<'a> {
         product_ids: &Option<Vec<&str>>,
         contract_expiry_type: Option<ContractExpiryType>,
     ) -> Result<Vec<Product>> {
+        let products_response = self
+            .list_products_page(
+                limit,
+                offset,
+                &product_type,
+                product_ids,
+                &contract_expiry_type,
+            )
+            .await?;
+
+        Ok(products_response.products)
+    }
+
+    /// List all the available currency pairs and return a stream of product batches.
+    ///
+    /// `limit` products per batch. The offset is increased by the size of each batch until all
+    /// `num_products` products are returned.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getproducts)
+    pub fn list_products_stream<'b>(
+        &'b self,
+        limit: Option<i32>,
+        product_type: Option<ProductType>,
+        product_ids: &'b Option<Vec<&'b str>>,
+        contract_expiry_type: Option<ContractExpiryType>,
+    ) -> impl Stream<Item = Result<Vec<Product>>> + 'b {
+        try_stream! {
+            let mut offset = 0;
+            loop {
+                let products_response = self
+                    .list_products_page(
+                        limit,
+                        Some(offset),
+                        &product_type,
+                        product_ids,
+                        &contract_expiry_type,
+                    )
+                    .await?;
+                let batch_size = products_response.products.len() as i32;
+                offset += batch_size;
+                let is_last_batch = batch_size == 0
+                    || limit.map_or(true, |limit| batch_size < limit)
+                    || offset >= products_response.num_products;
+                yield products_response.products;
+
+                if is_last_batch {
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn list_products_page(
+        &self,
+        limit: Option<i32>,
+        offset: Option<i32>,
+        product_type: &Option<ProductType>,
+        product_ids: &Option<Vec<&str>>,
+        contract_expiry_type: &Option<ContractExpiryType>,
+    ) -> Result<ProductsResponse> {
         let args = QueryArgs::new()
             .add_optional_scalar_arg("limit", &limit)
             .add_optional_scalar_arg("offset", &offset)
-            .add_optional_scalar_arg("product_type", &product_type)
+            .add_optional_scalar_arg("product_type", product_type)
             .add_optional_vec_args("product_ids", product_ids)
-            .add_optional_scalar_arg("contract_expiry_type", &contract_expiry_type);
-        let uri_string = MAIN_URL.to_string() + "/brokerage/products{?query*}";
+            .add_optional_scalar_arg("contract_expiry_type", contract_expiry_type);
+        let uri_string = self.base_url.clone() + "/brokerage/products{?query*}";
         let uri = UriTemplate::new(&uri_string)
             .set("query", args.get())
             .build();
-        let products_response: ProductsResponse = self.get(&uri).await?;
+        self.get(&uri).await
+    }
 
-        Ok(products_response.products)
+    /// Get the `top_n` products with the largest 24h volume, sorted by descending volume.
+    ///
+    /// Products without a known 24h volume are skipped.
+    pub async fn list_products_with_volume(&self, top_n: usize) -> Result<Vec<Product>> {
+        let products = self.list_products(None, None, None, &None, None).await?;
+        Ok(products::top_by_volume(products, top_n))
+    }
+
+    /// Get the products which orders can currently be placed on.
+    ///
+    /// Coinbase has no such filter: disabled, cancel only and view only products are filtered
+    /// out client-side, see [`Product::is_tradable`](`crate::products::Product::is_tradable`).
+    pub async fn list_tradable_products(
+        &self,
+        product_type: Option<ProductType>,
+    ) -> Result<Vec<Product>> {
+        let products = self
+            .list_products(None, None, product_type, &None, None)
+            .await?;
+        Ok(products::tradable_only(products))
+    }
+
+    /// Get the distinct currencies products can be quoted in (e.g. USD, USDC, BTC).
+    pub async fn quote_currencies(&self) -> Result<HashSet<String>> {
+        let products = self.list_products(None, None, None, &None, None).await?;
+        Ok(products::quote_currencies(&products))
+    }
+
+    /// Get the distinct base currencies of all products.
+    pub async fn base_currencies(&self) -> Result<HashSet<String>> {
+        let products = self.list_products(None, None, None, &None, None).await?;
+        Ok(products::base_currencies(&products))
     }
 
     /// Get information on a single product by product ID.
     ///
     /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getproduct)
     pub async fn get_product(&self, product_id: &str) -> Result<Product> {
-        let uri_string = MAIN_URL.to_string() + "/brokerage/products/{product_id}";
-        let uri = UriTemplate::new(&uri_string)
-            .set("product_id", product_id.to_string())
-            .build();
+        let uri = self.get_product_uri(product_id, None);
+        let product: Product = self.get(&uri).await?;
+        Ok(product)
+    }
+
+    /// Same as [`CbClient::get_product`], asking Coinbase to also populate the product's
+    /// tradability fields.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getproduct)
+    pub async fn get_product_with_tradability_status(&self, product_id: &str) -> Result<Product> {
+        let uri = self.get_product_uri(product_id, Some(true));
         let product: Product = self.get(&uri).await?;
         Ok(product)
     }
 
+    fn get_product_uri(&self, product_id: &str, get_tradability_status: Option<bool>) -> String {
+        let args = QueryArgs::new()
+            .add_optional_scalar_arg("get_tradability_status", &get_tradability_status);
+        let uri_string = self.base_url.clone() + "/brokerage/products/{product_id}{?query*}";
+        UriTemplate::new(&uri_string)
+            .set("product_id", product_id.to_string())
+            .set("query", args.get())
+            .build()
+    }
+
     /// Get rates for a single product by product ID, grouped in buckets.
     ///
+    /// Coinbase returns at most [`MAX_CANDLES_PER_REQUEST`](`crate::products::MAX_CANDLES_PER_REQUEST`)
+    /// candles per request: longer ranges are fetched in several sequential requests. Candles are
+    /// returned in chronological order.
+    ///
+    /// Coinbase expects `start` and `end` as UNIX timestamps in seconds, e.g. `1672531200`. As
+    /// Coinbase has not always been consistent about it, a request rejected with an
+    /// `INVALID_ARGUMENT` error about its `start` or `end` is sent again with RFC 3339 dates, e.g.
+    /// `2023-01-01T00:00:00Z`, which are then used for the remaining requests. The original error
+    /// is returned if the second request fails too.
+    ///
     /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getcandles)
     pub async fn get_product_candles(
         &self,
@@ -237,35 +878,115 @@ impl<'a> CbClient<'a> {
         end: &DateTime,
         granularity: Granularity,
     ) -> Result<Vec<Candle>> {
-        let uri_string = MAIN_URL.to_string() + "/brokerage/products/{product_id}/candles?start={start}&end={end}&granularity={granularity}";
-        let uri = UriTemplate::new(&uri_string)
+        let mut candles = Vec::new();
+        let mut time_format = CandleTimeFormat::UnixSeconds;
+        for (window_start, window_end) in products::candle_windows(start, end, &granularity)? {
+            let uri = self.get_product_candles_uri(
+                product_id,
+                &window_start,
+                &window_end,
+                &granularity,
+                time_format,
+            );
+            let mut candles_response: CandlesResponse = match self.get(&uri).await {
+                Err(original_error)
+                    if time_format == CandleTimeFormat::UnixSeconds
+                        && is_candle_time_error(&original_error) =>
+                {
+                    let uri = self.get_product_candles_uri(
+                        product_id,
+                        &window_start,
+                        &window_end,
+                        &granularity,
+                        CandleTimeFormat::Rfc3339,
+                    );
+                    // If RFC 3339 dates do not help either, the first error is the relevant one.
+                    let candles_response = match self.get(&uri).await {
+                        Ok(candles_response) => candles_response,
+                        Err(_) => return Err(original_error),
+                    };
+                    time_format = CandleTimeFormat::Rfc3339;
+                    candles_response
+                }
+                result => result?,
+            };
+            candles.append(&mut candles_response.candles);
+        }
+        Ok(products::merge_candles(candles))
+    }
+
+    fn get_product_candles_uri(
+        &self,
+        product_id: &str,
+        start: &DateTime,
+        end: &DateTime,
+        granularity: &Granularity,
+        time_format: CandleTimeFormat,
+    ) -> String {
+        let uri_string = self.base_url.clone()
+            + "/brokerage/products/{product_id}/candles?start={start}&end={end}&granularity={granularity}";
+        UriTemplate::new(&uri_string)
             .set("product_id", product_id.to_string())
-            .set("start", start.timestamp().to_string())
-            .set("end", end.timestamp().to_string())
+            .set("start", time_format.format(start))
+            .set("end", time_format.format(end))
             .set("granularity", granularity.to_string())
-            .build();
-        let candles_response: CandlesResponse = self.get(&uri).await?;
-        Ok(candles_response.candles)
+            .build()
     }
 
     /// Get snapshot information, by product ID, about the last trades (ticks), best bid/ask, and 24h volume.
     ///
     /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getmarkettrades)
     pub async fn get_market_trades(&self, product_id: &str, limit: i32) -> Result<MarketTrades> {
-        let uri_string =
-            MAIN_URL.to_string() + "/brokerage/products/{product_id}/ticker?limit={limit}";
-        let uri = UriTemplate::new(&uri_string)
-            .set("product_id", product_id.to_string())
-            .set("limit", limit.to_string())
-            .build();
+        let uri = self.get_market_trades_uri(product_id, limit, None, None);
         let market_trades: MarketTrades = self.get(&uri).await?;
         Ok(market_trades)
     }
 
-    /// Get a list of orders filtered by optional query parameters (product_id, order_status, etc).
+    /// Same as [`CbClient::get_market_trades`], restricted to the trades between `start` and
+    /// `end`, to page through history.
     ///
-    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_gethistoricalorders)
-    pub fn list_orders<'b>(
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getmarkettrades)
+    pub async fn get_market_trades_in_window(
+        &self,
+        product_id: &str,
+        limit: i32,
+        start: &DateTime,
+        end: &DateTime,
+    ) -> Result<MarketTrades> {
+        let uri = self.get_market_trades_uri(product_id, limit, Some(*start), Some(*end));
+        let market_trades: MarketTrades = self.get(&uri).await?;
+        Ok(market_trades)
+    }
+
+    fn get_market_trades_uri(
+        &self,
+        product_id: &str,
+        limit: i32,
+        start: Option<DateTime>,
+        end: Option<DateTime>,
+    ) -> String {
+        let args = QueryArgs::new()
+            .add_mandatory_arg("limit", &limit)
+            .add_optional_datetime_arg("start", &start)
+            .add_optional_datetime_arg("end", &end);
+        let uri_string = self.base_url.clone() + "/brokerage/products/{product_id}/ticker{?query*}";
+        UriTemplate::new(&uri_string)
+            .set("product_id", product_id.to_string())
+            .set("query", args.get())
+            .build()
+    }
+
+    /// Get a list of orders filtered by optional query parameters (product_id, order_status, etc).
+    ///
+    /// Prefer [list_orders_with_query()](`crate::client::CbClient::list_orders_with_query`),
+    /// whose named setters make it harder to mix up parameters.
+    ///
+    /// Leave `order_status` to `None` to list orders of all statuses. Several statuses are sent
+    /// as repeated keys (`order_status=OPEN&order_status=FILLED`), as expected by Coinbase.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_gethistoricalorders)
+    #[allow(clippy::too_many_arguments)]
+    pub fn list_orders<'b>(
         &'b self,
         product_id: Option<String>,
         order_status: Option<Vec<orders::Status>>,
@@ -280,61 +1001,90 @@ impl<'a> CbClient<'a> {
         order_placement_source: Option<orders::OrderPlacementSource>,
         contract_expiry_type: Option<ContractExpiryType>,
     ) -> impl Stream<Item = Result<Vec<Order>>> + 'b {
-        try_stream! {
-            let uri = Self::get_list_orders_uri(
-                &product_id,&order_status, &limit, &start_date, &end_date,
-                &deprecated_user_native_currency, &order_type, &order_side,
-                &cursor, &product_type, &order_placement_source, &contract_expiry_type);
+        self.list_orders_with_query(OrdersQuery {
+            product_id,
+            order_status,
+            limit,
+            start_date,
+            end_date,
+            deprecated_user_native_currency,
+            order_type,
+            order_side,
+            cursor,
+            product_type,
+            order_placement_source,
+            contract_expiry_type,
+        })
+    }
 
-            let mut orders_response: OrdersResponse = self.get(&uri).await?;
+    /// Get a list of orders filtered by an [`OrdersQuery`](`crate::orders::OrdersQuery`).
+    ///
+    /// ```no_run
+    /// # use coinbase_v3::{basic_oauth, client, orders};
+    /// # let oauth_cb_client = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
+    /// # let cb_client = client::CbClient::new(&oauth_cb_client);
+    /// let query = orders::OrdersQuery::new().product_id("BTC-USD").limit(50);
+    /// let orders_stream = cb_client.list_orders_with_query(query);
+    /// ```
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_gethistoricalorders)
+    pub fn list_orders_with_query<'b>(
+        &'b self,
+        mut query: OrdersQuery,
+    ) -> impl Stream<Item = Result<Vec<Order>>> + 'b {
+        try_stream! {
+            let mut orders_response = self.list_orders_page(&query).await?;
             yield orders_response.orders;
 
             while orders_response.has_next {
-                let cursor = Some(orders_response.cursor.clone());
-                let uri = Self::get_list_orders_uri(
-                    &product_id, &order_status, &limit, &start_date, &end_date,
-                    &deprecated_user_native_currency, &order_type, &order_side,
-                    &cursor, &product_type, &order_placement_source, &contract_expiry_type);
-                orders_response= self.get(&uri).await?;
+                query.cursor = Some(orders_response.cursor.clone());
+                orders_response = self.list_orders_page(&query).await?;
                 yield orders_response.orders;
             }
         }
     }
 
+    /// Get a single batch of orders filtered by an [`OrdersQuery`](`crate::orders::OrdersQuery`),
+    /// keeping the pagination information.
     ///
-    /// [Coinbase API reference]()
-    fn get_list_orders_uri(
-        product_id: &Option<String>,
-        order_status: &Option<Vec<orders::Status>>,
-        limit: &Option<i32>,
-        start_date: &Option<DateTime>,
-        end_date: &Option<DateTime>,
-        deprecated_user_native_currency: &Option<String>,
-        order_type: &Option<orders::OrderType>,
-        order_side: &Option<crate::products::Side>,
-        cursor: &Option<String>,
-        product_type: &Option<ProductType>,
-        order_placement_source: &Option<orders::OrderPlacementSource>,
-        contract_expiry_type: &Option<ContractExpiryType>,
-    ) -> String {
+    /// Contrary to [list_orders_with_query()](`crate::client::CbClient::list_orders_with_query`),
+    /// the returned [`OrdersResponse`] exposes `sequence`, `has_next` and `cursor`, so the
+    /// pagination can be driven manually, for instance to detect gaps between pages.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_gethistoricalorders)
+    pub async fn list_orders_page(&self, query: &OrdersQuery) -> Result<OrdersResponse> {
+        let uri = self.get_list_orders_uri(query);
+        self.get(&uri).await
+    }
+
+    /// List all open orders, of `product_id` or of all products, and return them in a single `Vec`.
+    ///
+    /// See [list_orders_with_query()](`crate::client::CbClient::list_orders_with_query`).
+    pub async fn list_open_orders(&self, product_id: Option<String>) -> Result<Vec<Order>> {
+        let mut query = OrdersQuery::new().order_status(vec![orders::Status::Open]);
+        query.product_id = product_id;
+        self.list_orders_with_query(query).try_concat().await
+    }
+
+    fn get_list_orders_uri(&self, query: &OrdersQuery) -> String {
         let args = QueryArgs::new()
-            .add_optional_scalar_arg("product_id", product_id)
-            .add_optional_vec_args("order_status", order_status)
-            .add_optional_scalar_arg("limit", limit)
-            .add_optional_datetime_arg("start_date", start_date) // "2021-05-31T09:59:59Z" RFC3339 ?
-            .add_optional_datetime_arg("end_date", end_date)
+            .add_optional_scalar_arg("product_id", &query.product_id)
+            .add_optional_vec_args("order_status", &query.order_status)
+            .add_optional_scalar_arg("limit", &query.limit)
+            .add_optional_datetime_arg("start_date", &query.start_date) // "2021-05-31T09:59:59Z" RFC3339 ?
+            .add_optional_datetime_arg("end_date", &query.end_date)
             .add_optional_scalar_arg(
                 "deprecated_user_native_currency",
-                deprecated_user_native_currency,
+                &query.deprecated_user_native_currency,
             )
-            .add_optional_scalar_arg("order_type", order_type)
-            .add_optional_scalar_arg("order_side", order_side)
-            .add_optional_scalar_arg("cursor", cursor)
-            .add_optional_scalar_arg("product_type", product_type)
-            .add_optional_scalar_arg("order_placement_source", order_placement_source)
-            .add_optional_scalar_arg("contract_expirty_type", contract_expiry_type);
+            .add_optional_scalar_arg("order_type", &query.order_type)
+            .add_optional_scalar_arg("order_side", &query.order_side)
+            .add_optional_scalar_arg("cursor", &query.cursor)
+            .add_optional_scalar_arg("product_type", &query.product_type)
+            .add_optional_scalar_arg("order_placement_source", &query.order_placement_source)
+            .add_optional_scalar_arg("contract_expiry_type", &query.contract_expiry_type);
 
-        let uri_string = MAIN_URL.to_string() + "/brokerage/orders/historical/batch{?query*}";
+        let uri_string = self.base_url.clone() + "/brokerage/orders/historical/batch{?query*}";
         let uri = UriTemplate::new(&uri_string)
             .set("query", args.get())
             .build();
@@ -343,6 +1093,9 @@ impl<'a> CbClient<'a> {
 
     /// Get a list of fills filtered by optional query parameters (product_id, order_id, etc).
     ///
+    /// Prefer [list_fills_with_query()](`crate::client::CbClient::list_fills_with_query`),
+    /// whose named setters make it harder to mix up parameters.
+    ///
     /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getfills)
     pub fn list_fills<'b>(
         &'b self,
@@ -353,37 +1106,72 @@ impl<'a> CbClient<'a> {
         limit: Option<i64>, // CB inconsistency: why i64 instead of i32 as all the others?
         cursor: Option<String>,
     ) -> impl Stream<Item = Result<Vec<orders::Fill>>> + 'b {
-        try_stream! {
-            let uri = Self::get_list_fills_uri(&order_id, &product_id, &start_sequence_timestamp, &end_sequence_timestamp, &limit, &cursor);
+        self.list_fills_with_query(FillsQuery {
+            order_id,
+            product_id,
+            start_sequence_timestamp,
+            end_sequence_timestamp,
+            limit,
+            cursor,
+        })
+    }
 
+    /// Get a list of fills filtered by a [`FillsQuery`](`crate::orders::FillsQuery`).
+    ///
+    /// ```no_run
+    /// # use coinbase_v3::{basic_oauth, client, orders};
+    /// # let oauth_cb_client = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
+    /// # let cb_client = client::CbClient::new(&oauth_cb_client);
+    /// let query = orders::FillsQuery::new().product_id("BTC-USD").limit(50);
+    /// let fills_stream = cb_client.list_fills_with_query(query);
+    /// ```
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getfills)
+    pub fn list_fills_with_query<'b>(
+        &'b self,
+        mut query: FillsQuery,
+    ) -> impl Stream<Item = Result<Vec<orders::Fill>>> + 'b {
+        try_stream! {
+            let uri = self.get_list_fills_uri(&query);
             let mut fills_response: FillsResponse = self.get(&uri).await?;
-            yield fills_response.fills;
 
-            while fills_response.cursor != "" {  // NO `has_next`; inconsistency from CB's api?
-                let cursor = Some(fills_response.cursor.clone());
-                let uri = Self::get_list_fills_uri(&order_id, &product_id, &start_sequence_timestamp, &end_sequence_timestamp, &limit, &cursor);
-                fills_response= self.get(&uri).await?;
+            // NO `has_next`; inconsistency from CB's api? The last page has an empty cursor, but
+            // Coinbase sometimes keeps returning the same cursor: stop as well when the cursor
+            // does not advance or on an empty page, instead of requesting the same page forever.
+            loop {
+                let is_last_page = fills_response.cursor.is_empty()
+                    || fills_response.fills.is_empty()
+                    || query.cursor.as_ref() == Some(&fills_response.cursor);
                 yield fills_response.fills;
+                if is_last_page {
+                    break;
+                }
+
+                query.cursor = Some(fills_response.cursor);
+                let uri = self.get_list_fills_uri(&query);
+                fills_response = self.get(&uri).await?;
             }
         }
     }
 
-    fn get_list_fills_uri(
-        order_id: &Option<String>,
-        product_id: &Option<String>,
-        start_sequence_timestamp: &Option<DateTime>,
-        end_sequence_timestamp: &Option<DateTime>,
-        limit: &Option<i64>, // CB inconsistency: why i64 instead of i32 as all the others?
-        cursor: &Option<String>,
-    ) -> String {
+    /// List all fills matching `query` and return them in a single `Vec`.
+    ///
+    /// See [list_fills_with_query()](`crate::client::CbClient::list_fills_with_query`).
+    pub async fn list_all_fills(&self, query: FillsQuery) -> Result<Vec<orders::Fill>> {
+        // Unlike other listings, fills responses have no `has_next`: the stream stops on the
+        // first page returned with an empty cursor.
+        self.list_fills_with_query(query).try_concat().await
+    }
+
+    fn get_list_fills_uri(&self, query: &FillsQuery) -> String {
         let args = QueryArgs::new()
-            .add_optional_scalar_arg("order_id", order_id)
-            .add_optional_scalar_arg("product_id", product_id)
-            .add_optional_scalar_arg("start_sequence_timestamp", start_sequence_timestamp)
-            .add_optional_scalar_arg("end_sequence_timestamp", end_sequence_timestamp)
-            .add_optional_scalar_arg("limit", limit)
-            .add_optional_scalar_arg("cursor", cursor);
-        let uri_string = MAIN_URL.to_string() + "/brokerage/orders/historical/fills{?query*}";
+            .add_optional_scalar_arg("order_id", &query.order_id)
+            .add_optional_scalar_arg("product_id", &query.product_id)
+            .add_optional_datetime_arg("start_sequence_timestamp", &query.start_sequence_timestamp)
+            .add_optional_datetime_arg("end_sequence_timestamp", &query.end_sequence_timestamp)
+            .add_optional_scalar_arg("limit", &query.limit)
+            .add_optional_scalar_arg("cursor", &query.cursor);
+        let uri_string = self.base_url.clone() + "/brokerage/orders/historical/fills{?query*}";
         let uri = UriTemplate::new(&uri_string)
             .set("query", args.get())
             .build();
@@ -394,7 +1182,7 @@ impl<'a> CbClient<'a> {
     ///
     /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_gethistoricalorder)
     pub async fn get_order(&self, order_id: &str) -> Result<Order> {
-        let uri_string = MAIN_URL.to_string() + "/brokerage/orders/historical/{order_id}";
+        let uri_string = self.base_url.clone() + "/brokerage/orders/historical/{order_id}";
         let uri = UriTemplate::new(&uri_string)
             .set("order_id", order_id.to_string())
             .build();
@@ -402,8 +1190,115 @@ impl<'a> CbClient<'a> {
         Ok(order_response.order)
     }
 
+    /// Get a single order along with all its fills.
+    pub async fn get_order_with_fills(&self, order_id: &str) -> Result<(Order, Vec<orders::Fill>)> {
+        let order = self.get_order(order_id).await?;
+        let fills = self
+            .list_all_fills(FillsQuery::new().order_id(order_id))
+            .await?;
+        Ok((order, fills))
+    }
+
+    /// Get the fill of order `order_id` with trade id `trade_id`, if any.
+    ///
+    /// Coinbase cannot filter fills by trade id, so the order's fills are listed until one
+    /// matches.
+    pub async fn get_fill_by_trade_id(
+        &self,
+        order_id: &str,
+        trade_id: &str,
+    ) -> Result<Option<orders::Fill>> {
+        let fills_stream = self.list_fills_with_query(FillsQuery::new().order_id(order_id));
+        pin_mut!(fills_stream);
+        while let Some(fills) = fills_stream.try_next().await? {
+            if let Some(fill) = fills.into_iter().find(|fill| fill.trade_id == trade_id) {
+                return Ok(Some(fill));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Wait for an IMMEDIATE_OR_CANCEL or FILL_OR_KILL order to settle and check its fill is
+    /// consistent with its time in force.
+    ///
+    /// The response to [create_order()](`crate::client::CbClient::create_order`) does not reflect
+    /// the final fill, and Coinbase settles orders asynchronously: the order is polled every
+    /// `poll_interval` until it is terminal, see
+    /// [wait_for_terminal()](`crate::client::CbClient::wait_for_terminal`). See
+    /// [`orders::verify_time_in_force`] for the performed checks.
+    ///
+    /// Returns a [`CbError::NotSettled`] error if the order is still not terminal after
+    /// `timeout`, and a [`CbError::Other`] error if its fill contradicts its time in force.
+    pub async fn verify_order_fill(
+        &self,
+        order_id: &str,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<Order> {
+        let order = self
+            .wait_for_terminal(order_id, poll_interval, timeout)
+            .await?;
+        orders::verify_time_in_force(&order)?;
+        Ok(order)
+    }
+
+    /// Poll the order `order_id` every `poll_interval` until its status is terminal, see
+    /// [`orders::Status::is_terminal`], and return it.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use coinbase_v3::{basic_oauth, client};
+    /// # use tokio_test;
+    /// # tokio_test::block_on(async {
+    /// # let oauth_cb_client = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
+    /// # let cb_client = client::CbClient::new(&oauth_cb_client);
+    /// let order = cb_client
+    ///     .wait_for_terminal("order_id", Duration::from_secs(1), Duration::from_secs(60))
+    ///     .await
+    ///     .unwrap();
+    /// # });
+    /// ```
+    ///
+    /// Returns a [`CbError::NotSettled`] error if the order is still not terminal after `timeout`.
+    pub async fn wait_for_terminal(
+        &self,
+        order_id: &str,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<Order> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let order = self.get_order(order_id).await?;
+            if order.status.is_terminal() {
+                return Ok(order);
+            }
+            if tokio::time::Instant::now() + poll_interval > deadline {
+                return Err(CbError::NotSettled {
+                    order_id: order_id.to_string(),
+                    status: order.status.to_string(),
+                });
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// Get the slippage of a filled order, in basis points, relative to `reference_price`.
+    ///
+    /// A positive value is unfavorable. See [`orders::slippage_bps`] for the sign convention.
+    pub async fn order_slippage(
+        &self,
+        order_id: &str,
+        reference_price: BigDecimal,
+    ) -> Result<BigDecimal> {
+        let order = self.get_order(order_id).await?;
+        Ok(orders::slippage_bps(&order, &reference_price)?)
+    }
+
     /// Get a summary of transactions with fee tiers, total volume, and fees.
     ///
+    /// See [get_transactions_summary_with_query()](`crate::client::CbClient::get_transactions_summary_with_query`)
+    /// to also filter by product.
+    ///
     /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_gettransactionsummary)
     pub async fn get_transactions_summary(
         &self,
@@ -413,46 +1308,501 @@ impl<'a> CbClient<'a> {
         product_type: Option<ProductType>,
         contract_expiry_type: Option<ContractExpiryType>,
     ) -> Result<fees::TransactionsSummary> {
+        self.get_transactions_summary_with_query(&TransactionsSummaryQuery {
+            start_date,
+            end_date,
+            user_native_currency,
+            product_type,
+            contract_expiry_type,
+            product_id: None,
+        })
+        .await
+    }
+
+    /// Get a summary of transactions filtered by a
+    /// [`TransactionsSummaryQuery`](`crate::fees::TransactionsSummaryQuery`).
+    ///
+    /// ```no_run
+    /// # use coinbase_v3::{basic_oauth, client, fees};
+    /// # async fn run() {
+    /// # let oauth_cb_client = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
+    /// # let cb_client = client::CbClient::new(&oauth_cb_client);
+    /// let query = fees::TransactionsSummaryQuery::new().product_id("BTC-USD");
+    /// let transactions_summary = cb_client
+    ///     .get_transactions_summary_with_query(&query)
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_gettransactionsummary)
+    pub async fn get_transactions_summary_with_query(
+        &self,
+        query: &TransactionsSummaryQuery,
+    ) -> Result<fees::TransactionsSummary> {
+        let uri = self.get_transactions_summary_uri(query);
+        let transaction_summary: fees::TransactionsSummary = self.get(&uri).await?;
+        Ok(transaction_summary)
+    }
+
+    fn get_transactions_summary_uri(&self, query: &TransactionsSummaryQuery) -> String {
         let args = QueryArgs::new()
-            .add_optional_datetime_arg("start_date", &start_date)
-            .add_optional_datetime_arg("end_date", &end_date)
-            .add_optional_scalar_arg("user_native_currency", &user_native_currency)
-            .add_optional_scalar_arg("product_type", &product_type)
-            .add_optional_scalar_arg("contract_expiry_type", &contract_expiry_type);
-        let uri_string = MAIN_URL.to_string() + "/brokerage/transaction_summary{?query*}";
+            .add_optional_datetime_arg("start_date", &query.start_date)
+            .add_optional_datetime_arg("end_date", &query.end_date)
+            .add_optional_scalar_arg("user_native_currency", &query.user_native_currency)
+            .add_optional_scalar_arg("product_type", &query.product_type)
+            .add_optional_scalar_arg("contract_expiry_type", &query.contract_expiry_type)
+            .add_optional_scalar_arg("product_id", &query.product_id);
+        let uri_string = self.base_url.clone() + "/brokerage/transaction_summary{?query*}";
+        UriTemplate::new(&uri_string)
+            .set("query", args.get())
+            .build()
+    }
+
+    /// Get the current time from Coinbase's servers.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getunixtime)
+    pub async fn get_server_time(&self) -> Result<ServerTime> {
+        let uri = self.base_url.clone() + "/brokerage/time";
+        self.get(&uri).await
+    }
+
+    /// Difference between Coinbase's server time and the local clock.
+    ///
+    /// Positive when the local clock is behind. The network latency is not compensated for.
+    pub async fn clock_skew(&self) -> Result<chrono::Duration> {
+        let server_time = self.get_server_time().await?;
+        Ok(server_time.iso - chrono::Utc::now())
+    }
+
+    /// Request a quote for converting `amount` from the `from_account` to the `to_account`.
+    ///
+    /// The returned trade has to be committed with
+    /// [commit_convert_trade()](`crate::client::CbClient::commit_convert_trade`).
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_createconvertquote)
+    pub async fn create_convert_quote(
+        &self,
+        from_account: &str,
+        to_account: &str,
+        amount: f64,
+    ) -> Result<ConvertTrade> {
+        let quote_request = convert::create_quote_request(from_account, to_account, amount)?;
+        let uri = self.base_url.clone() + "/brokerage/convert/quote";
+        let response: ConvertTradeResponse = self.post(&uri, &quote_request).await?;
+        Ok(response.trade)
+    }
+
+    /// Get a convert trade.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getconverttrade)
+    pub async fn get_convert_trade(
+        &self,
+        trade_id: &str,
+        from_account: &str,
+        to_account: &str,
+    ) -> Result<ConvertTrade> {
+        let args = QueryArgs::new()
+            .add_mandatory_arg("from_account", &from_account)
+            .add_mandatory_arg("to_account", &to_account);
+        let uri_string = self.base_url.clone() + "/brokerage/convert/trade/{trade_id}{?query*}";
         let uri = UriTemplate::new(&uri_string)
+            .set("trade_id", trade_id)
             .set("query", args.get())
             .build();
+        let response: ConvertTradeResponse = self.get(&uri).await?;
+        Ok(response.trade)
+    }
 
-        let transaction_summary: fees::TransactionsSummary = self.get(&uri).await?;
-        Ok(transaction_summary)
+    /// Commit a convert trade previously quoted with
+    /// [create_convert_quote()](`crate::client::CbClient::create_convert_quote`).
+    ///
+    /// !Warning! Using to this function might results in a financial loss.
+    /// Requires trading to be enabled with [allow_trading()](`crate::client::CbClient::allow_trading`).
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_commitconverttrade)
+    pub async fn commit_convert_trade(
+        &self,
+        trade_id: &str,
+        from_account: &str,
+        to_account: &str,
+    ) -> Result<ConvertTrade> {
+        self.ensure_trading_allowed()?;
+        let uri_string = self.base_url.clone() + "/brokerage/convert/trade/{trade_id}";
+        let uri = UriTemplate::new(&uri_string)
+            .set("trade_id", trade_id)
+            .build();
+        let trade_request = ConvertTradeRequest {
+            from_account: from_account.to_string(),
+            to_account: to_account.to_string(),
+        };
+        let response: ConvertTradeResponse = self.post(&uri, &trade_request).await?;
+        Ok(response.trade)
+    }
+
+    /// Get the balance summary of the futures account: buying power, margins and liquidation
+    /// thresholds.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getfcmbalancesummary)
+    pub async fn get_futures_balance_summary(&self) -> Result<FuturesBalanceSummary> {
+        let uri = self.base_url.clone() + "/brokerage/cfm/balance_summary";
+        let response: FuturesBalanceSummaryResponse = self.get(&uri).await?;
+        Ok(response.balance_summary)
+    }
+
+    /// List all the open futures positions.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getfcmpositions)
+    pub async fn list_futures_positions(&self) -> Result<Vec<FuturesPosition>> {
+        let uri = self.base_url.clone() + "/brokerage/cfm/positions";
+        let response: FuturesPositionsResponse = self.get(&uri).await?;
+        Ok(response.positions)
+    }
+
+    /// Get the open futures position for `product_id`.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getfcmposition)
+    pub async fn get_futures_position(&self, product_id: &str) -> Result<FuturesPosition> {
+        let uri_string = self.base_url.clone() + "/brokerage/cfm/positions/{product_id}";
+        let uri = UriTemplate::new(&uri_string)
+            .set("product_id", product_id)
+            .build();
+        let response: FuturesPositionResponse = self.get(&uri).await?;
+        Ok(response.position)
+    }
+
+    /// List all the user's portfolios, optionally filtered by type.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getportfolios)
+    pub async fn list_portfolios(
+        &self,
+        portfolio_type: Option<PortfolioType>,
+    ) -> Result<Vec<Portfolio>> {
+        let args = QueryArgs::new().add_optional_scalar_arg("portfolio_type", &portfolio_type);
+        let uri_string = self.base_url.clone() + "/brokerage/portfolios{?query*}";
+        let uri = UriTemplate::new(&uri_string)
+            .set("query", args.get())
+            .build();
+        let portfolios_response: PortfoliosResponse = self.get(&uri).await?;
+        Ok(portfolios_response.portfolios)
+    }
+
+    /// Get the breakdown of a portfolio: its balances, spot and perpetual future positions.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_getportfoliobreakdown)
+    pub async fn get_portfolio_breakdown(
+        &self,
+        portfolio_uuid: Uuid,
+    ) -> Result<PortfolioBreakdown> {
+        let uri = self.get_portfolio_uri(&portfolio_uuid);
+        let breakdown_response: PortfolioBreakdownResponse = self.get(&uri).await?;
+        Ok(breakdown_response.breakdown)
+    }
+
+    /// Create a new portfolio.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_createportfolio)
+    pub async fn create_portfolio(&self, name: &str) -> Result<Portfolio> {
+        let uri = self.base_url.clone() + "/brokerage/portfolios";
+        let portfolio_response: PortfolioResponse =
+            self.post(&uri, &HashMap::from([("name", name)])).await?;
+        Ok(portfolio_response.portfolio)
+    }
+
+    /// Rename a portfolio.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_editportfolio)
+    pub async fn edit_portfolio(&self, portfolio_uuid: Uuid, name: &str) -> Result<Portfolio> {
+        let uri = self.get_portfolio_uri(&portfolio_uuid);
+        let portfolio_response: PortfolioResponse =
+            self.put(&uri, &HashMap::from([("name", name)])).await?;
+        Ok(portfolio_response.portfolio)
+    }
+
+    /// Delete a portfolio.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_deleteportfolio)
+    pub async fn delete_portfolio(&self, portfolio_uuid: Uuid) -> Result<()> {
+        let uri = self.get_portfolio_uri(&portfolio_uuid);
+        // Coinbase answers with an empty object.
+        let _: serde_json::Value = self.delete(&uri).await?;
+        Ok(())
+    }
+
+    /// Move `amount` of `currency` from the `source` portfolio to the `target` one.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_moveportfoliofunds)
+    pub async fn move_portfolio_funds(
+        &self,
+        source: Uuid,
+        target: Uuid,
+        amount: f64,
+        currency: &str,
+    ) -> Result<MoveFundsResponse> {
+        let move_funds = portfolios::create_move_funds(source, target, amount, currency)?;
+        let uri = self.base_url.clone() + "/brokerage/portfolios/move_funds";
+        self.post(&uri, &move_funds).await
+    }
+
+    fn get_portfolio_uri(&self, portfolio_uuid: &Uuid) -> String {
+        let uri_string = self.base_url.clone() + "/brokerage/portfolios/{portfolio_uuid}";
+        UriTemplate::new(&uri_string)
+            .set("portfolio_uuid", portfolio_uuid.to_string())
+            .build()
     }
 
     /// Create an order with a specified product_id (asset-pair), side (buy/sell), etc.
     ///
     /// !Warning! Using to this function might results in a financial loss.
+    /// Requires trading to be enabled with [allow_trading()](`crate::client::CbClient::allow_trading`).
     ///  
     /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_postorder)
     pub async fn create_order(&self, order: &orders::OrderToSend) -> Result<CreateOrderResponse> {
-        let uri = MAIN_URL.to_string() + "/brokerage/orders";
+        self.ensure_trading_allowed()?;
+        let uri = self.base_url.clone() + "/brokerage/orders";
         self.post(&uri, order).await
     }
 
+    /// Create several orders, sending up to `max_concurrency` requests at once.
+    ///
+    /// Coinbase has no batch endpoint for orders: each one is created by its own request, and a
+    /// failure does not prevent the others from being created. The results are returned in the
+    /// same order as `orders`.
+    ///
+    /// !Warning! Using to this function might results in a financial loss.
+    /// Requires trading to be enabled with [allow_trading()](`crate::client::CbClient::allow_trading`).
+    pub async fn create_orders(
+        &self,
+        orders: &[orders::OrderToSend],
+        max_concurrency: usize,
+    ) -> Vec<Result<CreateOrderResponse>> {
+        futures::stream::iter(orders.iter().map(|order| self.create_order(order)))
+            .buffered(max_concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Preview an order: get its fees, slippage and potential errors without placing it.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_previeworder)
+    pub async fn preview_order(&self, order: &orders::OrderToSend) -> Result<PreviewOrderResponse> {
+        let uri = self.base_url.clone() + "/brokerage/orders/preview";
+        self.post(&uri, &order.as_preview()).await
+    }
+
+    /// Edit the price and/or size of an open limit order.
+    ///
+    /// !Warning! Using to this function might results in a financial loss.
+    /// Requires trading to be enabled with [allow_trading()](`crate::client::CbClient::allow_trading`).
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_editorder)
+    pub async fn edit_order(
+        &self,
+        order_id: &str,
+        price: Option<f64>,
+        size: Option<f64>,
+    ) -> Result<EditOrderResponse> {
+        self.ensure_trading_allowed()?;
+        let order_edit = orders::create_order_edit(order_id, price, size)?;
+        let uri = self.base_url.clone() + "/brokerage/orders/edit";
+        self.post(&uri, &order_edit).await
+    }
+
+    /// Close the futures position on `product_id`, either entirely or only `size` contracts.
+    ///
+    /// !Warning! Using to this function might results in a financial loss.
+    /// Requires trading to be enabled with [allow_trading()](`crate::client::CbClient::allow_trading`).
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_closeposition)
+    pub async fn close_position(
+        &self,
+        product_id: &str,
+        size: Option<f64>,
+    ) -> Result<CreateOrderResponse> {
+        self.ensure_trading_allowed()?;
+        let close_position = create_close_position(product_id, size)?;
+        let uri = self.base_url.clone() + "/brokerage/orders/close_position";
+        self.post(&uri, &close_position).await
+    }
+
     /// Initiate cancel requests for one or more orders.
     ///
     /// /// !Warning! Using to this function might results in a financial loss.
+    /// Requires trading to be enabled with [allow_trading()](`crate::client::CbClient::allow_trading`).
     ///  
     /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/reference/retailbrokerageapi_cancelorders)
     pub async fn cancel_order(&self, order_ids: &Vec<String>) -> Result<Vec<CancelOrderResponse>> {
+        self.ensure_trading_allowed()?;
         let mut m = HashMap::<&str, &Vec<String>>::new();
         m.insert("order_ids", order_ids);
 
-        let uri = MAIN_URL.to_string() + "/brokerage/orders/batch_cancel";
+        let uri = self.base_url.clone() + "/brokerage/orders/batch_cancel";
         let response = self
             .post::<HashMap<&str, &Vec<String>>, CancelOrdersResponse>(&uri, &m)
             .await?;
         Ok(response.results)
     }
+
+    /// Same as [`CbClient::cancel_order`], with the results split into the IDs of the cancelled
+    /// orders and the IDs of the orders that could not be cancelled, along with the reason.
+    ///
+    /// !Warning! Using to this function might results in a financial loss.
+    /// Requires trading to be enabled with [allow_trading()](`crate::client::CbClient::allow_trading`).
+    pub async fn cancel_order_checked(
+        &self,
+        order_ids: &Vec<String>,
+    ) -> Result<(Vec<String>, Vec<(String, CancelOrderFailureReason)>)> {
+        let results = self.cancel_order(order_ids).await?;
+        Ok(orders::split_cancel_results(results))
+    }
+
+    /// Cancel all open orders, optionally only for a single product, in batches of
+    /// [`MAX_CANCEL_BATCH_SIZE`](`crate::orders::MAX_CANCEL_BATCH_SIZE`) orders.
+    ///
+    /// Returns the result of each batch cancel request, in order: a failed batch does not stop the
+    /// following ones, nor hide the orders already cancelled. Fails only if the open orders can
+    /// not be listed.
+    ///
+    /// !Warning! Using to this function might results in a financial loss.
+    /// Requires trading to be enabled with [allow_trading()](`crate::client::CbClient::allow_trading`).
+    pub async fn cancel_all_open_orders(
+        &self,
+        product_id: Option<String>,
+    ) -> Result<Vec<Result<Vec<CancelOrderResponse>>>> {
+        self.ensure_trading_allowed()?;
+        let mut query = OrdersQuery::new().order_status(vec![orders::Status::Open]);
+        if let Some(product_id) = &product_id {
+            query = query.product_id(product_id);
+        }
+        let open_orders = self.list_orders_with_query(query).try_concat().await?;
+
+        let mut results = Vec::new();
+        for batch in orders::cancel_batches(&open_orders, product_id.as_deref()) {
+            results.push(self.cancel_order(&batch).await);
+        }
+        Ok(results)
+    }
+
+    /// Cancel all open orders for a single product, see
+    /// [cancel_all_open_orders()](`crate::client::CbClient::cancel_all_open_orders`).
+    ///
+    /// !Warning! Using to this function might results in a financial loss.
+    /// Requires trading to be enabled with [allow_trading()](`crate::client::CbClient::allow_trading`).
+    pub async fn cancel_orders_for_product(
+        &self,
+        product_id: &str,
+    ) -> Result<Vec<Result<Vec<CancelOrderResponse>>>> {
+        self.cancel_all_open_orders(Some(product_id.to_string()))
+            .await
+    }
+}
+
+/// Parse the `Retry-After` header, given either in seconds or as an HTTP date.
+fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
+/// Whether Coinbase rejected the `start` or `end` of a candles request, e.g. `INVALID_ARGUMENT`
+/// with the message `invalid start`.
+fn is_candle_time_error(error: &CbError) -> bool {
+    match error {
+        CbError::Coinbase { error, .. } => {
+            error.error == "INVALID_ARGUMENT"
+                && error
+                    .message
+                    .to_lowercase()
+                    .split(|c: char| !c.is_ascii_alphanumeric())
+                    .any(|word| word == "start" || word == "end")
+        }
+        _ => false,
+    }
+}
+
+/// How candles' `start` and `end` are written in the query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CandleTimeFormat {
+    /// UNIX timestamp in seconds, as documented by Coinbase.
+    UnixSeconds,
+    /// RFC 3339 date, as expected by Coinbase's other endpoints.
+    Rfc3339,
+}
+
+impl CandleTimeFormat {
+    fn format(&self, datetime: &DateTime) -> String {
+        match self {
+            CandleTimeFormat::UnixSeconds => datetime.timestamp().to_string(),
+            CandleTimeFormat::Rfc3339 => {
+                datetime.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+            }
+        }
+    }
+}
+
+/// Path of `request_url`, without host nor query.
+#[cfg(feature = "tracing")]
+fn request_path(request_url: &str) -> String {
+    url::Url::parse(request_url)
+        .map(|url| url.path().to_string())
+        .unwrap_or_default()
+}
+
+/// Server errors worth retrying, as they are usually temporary.
+fn is_transient_status(status: reqwest::StatusCode) -> bool {
+    matches!(
+        status,
+        reqwest::StatusCode::INTERNAL_SERVER_ERROR
+            | reqwest::StatusCode::BAD_GATEWAY
+            | reqwest::StatusCode::SERVICE_UNAVAILABLE
+            | reqwest::StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Exponential backoff with jitter: a random delay between half and all of `base_delay * 2^attempt`.
+pub(crate) fn backoff_delay(base_delay: Duration, attempt: u32) -> Duration {
+    let max_delay = base_delay.saturating_mul(2u32.saturating_pow(attempt));
+    let jitter: f64 = rand::thread_rng().gen_range(0.5..=1.0);
+    max_delay.mul_f64(jitter)
+}
+
+/// Token bucket holding up to `requests_per_second` tokens, refilled continuously.
+///
+/// Implemented as a virtual scheduling algorithm: instead of counting tokens, it tracks the time
+/// at which the bucket will be full again.
+struct RateLimiter {
+    interval: Duration,
+    burst: Duration,
+    full_at: tokio::sync::Mutex<tokio::time::Instant>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_second: u32) -> Self {
+        let requests_per_second = requests_per_second.max(1);
+        let interval = Duration::from_secs(1) / requests_per_second;
+        RateLimiter {
+            interval,
+            burst: interval * (requests_per_second - 1),
+            full_at: tokio::sync::Mutex::new(tokio::time::Instant::now()),
+        }
+    }
+
+    /// Wait until a token is available and take it.
+    async fn acquire(&self) {
+        let ready_at = {
+            let mut full_at = self.full_at.lock().await;
+            let now = tokio::time::Instant::now();
+            let next_full_at = std::cmp::max(*full_at, now) + self.interval;
+            *full_at = next_full_at;
+            next_full_at - self.interval - self.burst
+        };
+        tokio::time::sleep_until(ready_at).await;
+    }
 }
 
 /// Store date for passing them to a UriTemplate builder
@@ -505,3 +1855,1396 @@ impl QueryArgs {
         self
     }
 }
+
+//=========== TESTS ===========================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oauth2::AccessToken;
+    use std::str::FromStr;
+    use wiremock::matchers::{body_json, body_partial_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    struct DummyTokenProvider;
+
+    impl AccessTokenProvider for DummyTokenProvider {
+        fn access_token(&self) -> Result<AccessToken> {
+            Ok(AccessToken::new("dummy".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_http_client() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("x-custom", "foo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-custom", "foo".parse().unwrap());
+        let http_client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::with_http_client(&provider, http_client);
+
+        let result: Result<serde_json::Value> = cb_client.get(&server.uri()).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_with_connection_pool() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("user-agent", "my-bot/1.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider)
+            .with_user_agent("my-bot/1.0")
+            .with_connection_pool(1, Some(Duration::from_secs(30)))
+            .unwrap();
+        for _ in 0..2 {
+            let result: Result<serde_json::Value> = cb_client.get(&server.uri()).await;
+            assert!(result.is_ok());
+        }
+    }
+
+    #[test]
+    fn test_with_connection_pool_refuses_custom_http_client() {
+        let provider = DummyTokenProvider;
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap();
+        let result = CbClient::with_http_client(&provider, http_client)
+            .with_connection_pool(1, Some(Duration::from_secs(30)));
+        assert!(matches!(result, Err(CbError::Other(_))));
+    }
+
+    #[tokio::test]
+    async fn test_user_agent() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("user-agent", DEFAULT_USER_AGENT))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(header("user-agent", "my-bot/1.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider);
+        let result: Result<serde_json::Value> = cb_client.get(&server.uri()).await;
+        assert!(result.is_ok());
+
+        let cb_client = CbClient::new(&provider).with_user_agent("my-bot/1.0");
+        let result: Result<serde_json::Value> =
+            cb_client.post(&server.uri(), &serde_json::json!({})).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_trading_disabled_by_default() {
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider);
+
+        let order = orders::create_market_order("BTC-USD", orders::OrderSide::Buy, 10.0).unwrap();
+        let result = cb_client.create_order(&order).await;
+        assert!(matches!(result, Err(CbError::TradingDisabled)));
+
+        let result = cb_client.edit_order("foo", Some(1.0), None).await;
+        assert!(matches!(result, Err(CbError::TradingDisabled)));
+
+        let result = cb_client.cancel_order(&vec!["foo".to_string()]).await;
+        assert!(matches!(result, Err(CbError::TradingDisabled)));
+
+        let result = cb_client.cancel_all_open_orders(None).await;
+        assert!(matches!(result, Err(CbError::TradingDisabled)));
+
+        let result = cb_client.close_position("BIT-28JUL23-CDE", None).await;
+        assert!(matches!(result, Err(CbError::TradingDisabled)));
+
+        let result = cb_client.commit_convert_trade("foo", "bar", "baz").await;
+        assert!(matches!(result, Err(CbError::TradingDisabled)));
+    }
+
+    #[test]
+    fn test_list_orders_uri_contract_expiry_type() {
+        let query = OrdersQuery::new().contract_expiry_type(ContractExpiryType::Expiring);
+        let provider = DummyTokenProvider;
+        let uri = CbClient::new(&provider).get_list_orders_uri(&query);
+        assert!(uri.contains("contract_expiry_type=EXPIRING"));
+    }
+
+    #[test]
+    fn test_list_orders_uri() {
+        let query = OrdersQuery::new()
+            .product_id("BTC-USD")
+            .order_status(vec![orders::Status::Open, orders::Status::Filled])
+            .limit(50)
+            .start_date(DateTime::from_str("2023-01-01T00:00:00Z").unwrap())
+            .order_type(orders::OrderType::Limit)
+            .order_side(orders::OrderSide::Sell)
+            .product_type(ProductType::Spot);
+        let provider = DummyTokenProvider;
+        assert_eq!(
+            CbClient::new(&provider).get_list_orders_uri(&query),
+            MAIN_URL.to_string()
+                + "/brokerage/orders/historical/batch?product_id=BTC-USD&order_status=OPEN"
+                + "&order_status=FILLED&limit=50&start_date=2023-01-01T00%3A00%3A00Z"
+                + "&order_type=LIMIT&order_side=SELL&product_type=SPOT"
+        );
+    }
+
+    #[test]
+    fn test_list_orders_uri_order_status() {
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider);
+        let query =
+            OrdersQuery::new().order_status(vec![orders::Status::Open, orders::Status::Filled]);
+        assert_eq!(
+            cb_client.get_list_orders_uri(&query),
+            MAIN_URL.to_string()
+                + "/brokerage/orders/historical/batch?order_status=OPEN&order_status=FILLED"
+        );
+
+        // No filter means all statuses.
+        let uri = cb_client.get_list_orders_uri(&OrdersQuery::new());
+        assert!(!uri.contains("order_status"));
+    }
+
+    #[test]
+    fn test_allow_trading() {
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider);
+        assert!(cb_client.ensure_trading_allowed().is_err());
+
+        let cb_client = cb_client.allow_trading(true);
+        assert!(cb_client.ensure_trading_allowed().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_provider() {
+        let oauth_cb_client =
+            crate::basic_oauth::OAuthCbClient::new("", "", "http://localhost:3001").unwrap();
+        let cb_client = CbClient::new(&oauth_cb_client);
+
+        let result = cb_client.get_product("BTC-USD").await;
+        assert!(matches!(result, Err(CbError::Auth(_))));
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        let base_delay = Duration::from_millis(100);
+        for attempt in 0..4 {
+            let max_delay = base_delay * 2u32.pow(attempt);
+            let delay = backoff_delay(base_delay, attempt);
+            assert!(delay >= max_delay / 2 && delay <= max_delay);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_retries_transient_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"ok": true}"#))
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_retry(2, Duration::from_millis(1));
+        let result: serde_json::Value = cb_client.get(&server.uri()).await.unwrap();
+        assert_eq!(result["ok"], true);
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(r#"{"ok": true}"#)
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_timeout(Duration::from_millis(50));
+        let result = cb_client.get::<serde_json::Value>(&server.uri()).await;
+        assert!(matches!(result, Err(CbError::Timeout)));
+
+        let cb_client = CbClient::new(&provider).with_timeout(Duration::from_secs(5));
+        let result: serde_json::Value = cb_client.get(&server.uri()).await.unwrap();
+        assert_eq!(result["ok"], true);
+    }
+
+    #[tokio::test]
+    async fn test_create_orders() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/brokerage/orders"))
+            .and(body_partial_json(
+                serde_json::json!({"product_id": "ETH-USD"}),
+            ))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "error": "INVALID_ARGUMENT",
+                "code": 3,
+                "message": "invalid order",
+                "details": {"type_url": "", "value": 0}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/brokerage/orders"))
+            .respond_with(|request: &wiremock::Request| {
+                let order: serde_json::Value = request.body_json().unwrap();
+                // The first order is answered last.
+                let delay = if order["product_id"] == "BTC-USD" {
+                    200
+                } else {
+                    0
+                };
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({
+                        "success": true,
+                        "failure_reason": "UNKNOWN_FAILURE_REASON",
+                        "order_id": order["product_id"],
+                        "order_configuration": order["order_configuration"]
+                    }))
+                    .set_delay(Duration::from_millis(delay))
+            })
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let orders: Vec<orders::OrderToSend> = ["BTC-USD", "ETH-USD", "SOL-USD", "ADA-USD"]
+            .iter()
+            .map(|product_id| {
+                orders::create_market_order(product_id, crate::products::Side::Buy, 10.0).unwrap()
+            })
+            .collect();
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider)
+            .with_base_url(&server.uri())
+            .allow_trading(true);
+        let results = cb_client.create_orders(&orders, 2).await;
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().unwrap().order_id, "BTC-USD");
+        assert!(matches!(results[1], Err(CbError::Coinbase { .. })));
+        assert_eq!(results[2].as_ref().unwrap().order_id, "SOL-USD");
+        assert_eq!(results[3].as_ref().unwrap().order_id, "ADA-USD");
+
+        let cb_client = cb_client.allow_trading(false);
+        let results = cb_client.create_orders(&orders[..1], 2).await;
+        assert!(matches!(results[0], Err(CbError::TradingDisabled)));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"ok": true}"#))
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_rate_limit(10);
+        let cloned_client = cb_client.clone();
+        let start = std::time::Instant::now();
+        // A burst of 10 requests, then 5 more spaced by 100ms, shared between both clients.
+        for i in 0..15 {
+            let client = if i % 2 == 0 {
+                &cb_client
+            } else {
+                &cloned_client
+            };
+            let _: serde_json::Value = client.get(&server.uri()).await.unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(450));
+    }
+
+    #[tokio::test]
+    async fn test_get_gives_up_after_max_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_retry(2, Duration::from_millis(1));
+        let result = cb_client.get::<serde_json::Value>(&server.uri()).await;
+        assert!(result.is_err());
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_post_not_retried_by_default() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_retry(2, Duration::from_millis(1));
+        let result = cb_client
+            .post::<_, serde_json::Value>(&server.uri(), &serde_json::json!({}))
+            .await;
+        assert!(result.is_err());
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+        let cb_client = cb_client.retry_posts(true);
+        let _ = cb_client
+            .post::<_, serde_json::Value>(&server.uri(), &serde_json::json!({}))
+            .await;
+        assert_eq!(server.received_requests().await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_unpack_rate_limited_response() {
+        let response = http::Response::builder()
+            .status(429)
+            .header("Retry-After", "7")
+            .body(r#"{"error": "rate limited"}"#)
+            .unwrap();
+        let result = CbClient::unpack_response::<serde_json::Value>("GET", response.into()).await;
+        assert!(matches!(
+            result,
+            Err(CbError::RateLimited { retry_after: Some(d) }) if d == Duration::from_secs(7)
+        ));
+
+        let response = http::Response::builder().status(429).body("").unwrap();
+        let result = CbClient::unpack_response::<serde_json::Value>("GET", response.into()).await;
+        assert!(matches!(
+            result,
+            Err(CbError::RateLimited { retry_after: None })
+        ));
+    }
+
+    #[test]
+    fn test_portfolio_uri() {
+        let portfolio_uuid = Uuid::from_str("8a2ac7ea-3c84-4fb3-9e55-0b9c1e2f7a11").unwrap();
+        let provider = DummyTokenProvider;
+        assert_eq!(
+            CbClient::new(&provider).get_portfolio_uri(&portfolio_uuid),
+            "https://api.coinbase.com/api/v3/brokerage/portfolios/8a2ac7ea-3c84-4fb3-9e55-0b9c1e2f7a11"
+        );
+    }
+
+    #[test]
+    fn test_product_book_uri() {
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider);
+        assert_eq!(
+            cb_client.get_product_book_uri(
+                "BTC-USD",
+                Some(10),
+                Some(BigDecimal::from_str("0.05").unwrap())
+            ),
+            MAIN_URL.to_string()
+                + "/brokerage/product_book/?product_id=BTC-USD&limit=10&aggregation_price_increment=0.05"
+        );
+        assert_eq!(
+            cb_client.get_product_book_uri("BTC-USD", None, None),
+            MAIN_URL.to_string() + "/brokerage/product_book/?product_id=BTC-USD"
+        );
+    }
+
+    #[test]
+    fn test_market_trades_uri() {
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider);
+        assert_eq!(
+            cb_client.get_market_trades_uri(
+                "BTC-USD",
+                100,
+                Some(DateTime::from_str("2023-01-01T00:00:00Z").unwrap()),
+                Some(DateTime::from_str("2023-01-01T01:00:00Z").unwrap())
+            ),
+            MAIN_URL.to_string()
+                + "/brokerage/products/BTC-USD/ticker?limit=100&start=2023-01-01T00%3A00%3A00Z"
+                + "&end=2023-01-01T01%3A00%3A00Z"
+        );
+        assert_eq!(
+            cb_client.get_market_trades_uri("BTC-USD", 3, None, None),
+            MAIN_URL.to_string() + "/brokerage/products/BTC-USD/ticker?limit=3"
+        );
+    }
+
+    fn product_json(product_id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "product_id": product_id,
+            "price": "1",
+            "volume_24h": "6",
+            "volume_percentage_change_24h": "0",
+            "base_increment": "1",
+            "quote_increment": "0.01",
+            "quote_min_size": "1",
+            "quote_max_size": "1000",
+            "base_min_size": "1",
+            "base_max_size": "1000",
+            "base_name": "Base",
+            "quote_name": "Quote",
+            "watched": false,
+            "is_disabled": false,
+            "new": false,
+            "status": "online",
+            "cancel_only": false,
+            "limit_only": false,
+            "post_only": false,
+            "trading_disabled": false,
+            "auction_mode": false,
+            "product_type": "SPOT",
+            "quote_currency_id": "USD",
+            "base_currency_id": "BASE",
+            "fcm_trading_session_details": null,
+            "mid_market_price": "",
+            "alias": "",
+            "alias_to": [],
+            "base_display_symbol": "BASE",
+            "quote_display_symbol": "USD",
+            "view_only": false,
+            "price_increment": "0.01"
+        })
+    }
+
+    fn order_json(order_id: &str, status: &str) -> serde_json::Value {
+        serde_json::json!({
+            "order_id": order_id,
+            "product_id": "BTC-USD",
+            "user_id": "2222-000000-000000",
+            "order_configuration": {
+                "limit_limit_gtc": {
+                    "base_size": "0.002",
+                    "limit_price": "25000",
+                    "post_only": false
+                }
+            },
+            "side": "BUY",
+            "client_order_id": "11111-000000-000000",
+            "status": status,
+            "time_in_force": "GOOD_UNTIL_CANCELLED",
+            "created_time": "2023-06-01T10:00:00Z",
+            "completion_percentage": "100",
+            "filled_size": "0.002",
+            "average_filled_price": "25000",
+            "fee": "",
+            "number_of_fills": "2",
+            "filled_value": "50",
+            "pending_cancel": false,
+            "size_in_quote": false,
+            "total_fees": "0.3",
+            "size_inclusive_of_fees": false,
+            "total_value_after_fees": "50.3",
+            "order_type": "LIMIT",
+            "settled": true,
+            "product_type": "SPOT",
+            "order_placement_source": "RETAIL_ADVANCED",
+            "outstanding_hold_amount": "0",
+            "is_liquidation": false
+        })
+    }
+
+    fn fill_json(trade_id: &str, order_id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "entry_id": format!("entry-{}", trade_id),
+            "trade_id": trade_id,
+            "order_id": order_id,
+            "trade_time": "2023-06-01T10:00:01Z",
+            "trade_type": "FILL",
+            "price": "25000",
+            "size": "0.001",
+            "commission": "0.15",
+            "product_id": "BTC-USD",
+            "sequence_timestamp": "2023-06-01T10:00:01Z",
+            "liquidity_indicator": "MAKER",
+            "size_in_quote": false,
+            "user_id": "2222-000000-000000",
+            "side": "BUY"
+        })
+    }
+
+    #[tokio::test]
+    async fn test_list_all_fills() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/fills"))
+            .and(query_param("cursor", "page-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "fills": [fill_json("trade-3", "order-2")],
+                "cursor": ""
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/fills"))
+            .and(query_param("product_id", "BTC-USD"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "fills": [fill_json("trade-1", "order-1"), fill_json("trade-2", "order-1")],
+                "cursor": "page-2"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let fills = cb_client
+            .list_all_fills(FillsQuery::new().product_id("BTC-USD"))
+            .await
+            .unwrap();
+        let trade_ids: Vec<&str> = fills.iter().map(|fill| fill.trade_id.as_str()).collect();
+        assert_eq!(trade_ids, vec!["trade-1", "trade-2", "trade-3"]);
+    }
+
+    #[test]
+    fn test_product_candles_uri() {
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider);
+        let start = DateTime::from_str("2023-01-01T00:00:00Z").unwrap();
+        let end = DateTime::from_str("2023-01-02T00:00:00Z").unwrap();
+        assert_eq!(
+            cb_client.get_product_candles_uri(
+                "BTC-USD",
+                &start,
+                &end,
+                &Granularity::OneHour,
+                CandleTimeFormat::UnixSeconds
+            ),
+            MAIN_URL.to_string()
+                + "/brokerage/products/BTC-USD/candles?start=1672531200&end=1672617600"
+                + "&granularity=ONE_HOUR"
+        );
+        assert_eq!(
+            cb_client.get_product_candles_uri(
+                "BTC-USD",
+                &start,
+                &end,
+                &Granularity::OneHour,
+                CandleTimeFormat::Rfc3339
+            ),
+            MAIN_URL.to_string()
+                + "/brokerage/products/BTC-USD/candles?start=2023-01-01T00%3A00%3A00Z"
+                + "&end=2023-01-02T00%3A00%3A00Z&granularity=ONE_HOUR"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_product_candles_rfc3339_fallback() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/products/BTC-USD/candles"))
+            .and(query_param("start", "1672531200"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "error": "INVALID_ARGUMENT",
+                "code": 3,
+                "message": "invalid start",
+                "details": {"type_url": "", "value": 0}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/products/BTC-USD/candles"))
+            .and(query_param("start", "2023-01-01T00:00:00Z"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "candles": [{
+                    "start": "1672531200",
+                    "low": "16500",
+                    "high": "16600",
+                    "open": "16550",
+                    "close": "16580",
+                    "volume": "12.5"
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let candles = cb_client
+            .get_product_candles(
+                "BTC-USD",
+                &DateTime::from_str("2023-01-01T00:00:00Z").unwrap(),
+                &DateTime::from_str("2023-01-01T01:00:00Z").unwrap(),
+                Granularity::OneHour,
+            )
+            .await
+            .unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].start, "1672531200");
+    }
+
+    #[tokio::test]
+    async fn test_product_candles_rfc3339_fallback_fails() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/products/BTC-USD/candles"))
+            .and(query_param("start", "1672531200"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "error": "INVALID_ARGUMENT",
+                "code": 3,
+                "message": "invalid start",
+                "details": {"type_url": "", "value": 0}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/products/BTC-USD/candles"))
+            .and(query_param("start", "2023-01-01T00:00:00Z"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "error": "INVALID_ARGUMENT",
+                "code": 3,
+                "message": "malformed date",
+                "details": {"type_url": "", "value": 0}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let result = cb_client
+            .get_product_candles(
+                "BTC-USD",
+                &DateTime::from_str("2023-01-01T00:00:00Z").unwrap(),
+                &DateTime::from_str("2023-01-01T01:00:00Z").unwrap(),
+                Granularity::OneHour,
+            )
+            .await;
+        match result {
+            Err(CbError::Coinbase { error, .. }) => assert_eq!(error.message, "invalid start"),
+            _ => panic!("expected the first Coinbase error, got {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_product_candles_no_fallback_on_other_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/products/BTC-USD/candles"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "error": "INVALID_ARGUMENT",
+                "code": 3,
+                "message": "invalid granularity",
+                "details": {"type_url": "", "value": 0}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let result = cb_client
+            .get_product_candles(
+                "BTC-USD",
+                &DateTime::from_str("2023-01-01T00:00:00Z").unwrap(),
+                &DateTime::from_str("2023-01-01T01:00:00Z").unwrap(),
+                Granularity::OneHour,
+            )
+            .await;
+        assert!(matches!(result, Err(CbError::Coinbase { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_orders_for_product() {
+        let server = MockServer::start().await;
+        let mut open_orders: Vec<serde_json::Value> = (0..150)
+            .map(|i| order_json(&format!("order-{}", i), "OPEN"))
+            .collect();
+        let mut other_product_order = order_json("order-eth", "OPEN");
+        other_product_order["product_id"] = "ETH-USD".into();
+        open_orders.push(other_product_order);
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/batch"))
+            .and(query_param("product_id", "BTC-USD"))
+            .and(query_param("order_status", "OPEN"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "orders": open_orders,
+                "sequence": "0",
+                "has_next": false,
+                "cursor": ""
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let batch_size = |size: usize| {
+            move |request: &wiremock::Request| {
+                let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                body["order_ids"].as_array().unwrap().len() == size
+            }
+        };
+        let first_batch_results: Vec<serde_json::Value> = (0..100)
+            .map(|i| serde_json::json!({"success": true, "order_id": format!("order-{}", i)}))
+            .collect();
+        Mock::given(method("POST"))
+            .and(path("/brokerage/orders/batch_cancel"))
+            .and(batch_size(100))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "results": first_batch_results })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/brokerage/orders/batch_cancel"))
+            .and(batch_size(50))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider)
+            .with_base_url(&server.uri())
+            .allow_trading(true);
+        let results = cb_client
+            .cancel_orders_for_product("BTC-USD")
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        let cancelled = results[0].as_ref().unwrap();
+        assert_eq!(cancelled.len(), 100);
+        assert_eq!(cancelled[99].order_id, "order-99");
+        assert!(results[1].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_orders_page() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/batch"))
+            .and(query_param("cursor", "page-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "orders": [order_json("order-3", "FILLED")],
+                "sequence": "42",
+                "has_next": true,
+                "cursor": "page-3"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let response = cb_client
+            .list_orders_page(&OrdersQuery::new().cursor("page-2"))
+            .await
+            .unwrap();
+        assert_eq!(response.sequence, "42");
+        assert!(response.has_next);
+        assert_eq!(response.cursor, "page-3");
+        assert_eq!(response.orders.len(), 1);
+        assert_eq!(response.orders[0].order_id, "order-3");
+    }
+
+    #[tokio::test]
+    async fn test_list_open_orders() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/batch"))
+            .and(query_param("cursor", "page-2"))
+            .and(query_param("order_status", "OPEN"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "orders": [order_json("order-3", "OPEN")],
+                "sequence": "0",
+                "has_next": false,
+                "cursor": ""
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/batch"))
+            .and(query_param("order_status", "OPEN"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "orders": [order_json("order-1", "OPEN"), order_json("order-2", "OPEN")],
+                "sequence": "0",
+                "has_next": true,
+                "cursor": "page-2"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let orders = cb_client.list_open_orders(None).await.unwrap();
+        let order_ids: Vec<&str> = orders.iter().map(|order| order.order_id.as_str()).collect();
+        assert_eq!(order_ids, vec!["order-1", "order-2", "order-3"]);
+        for request in server.received_requests().await.unwrap() {
+            assert!(!request
+                .url
+                .query_pairs()
+                .any(|(key, _)| key == "product_id"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_fills_stops_on_repeated_cursor() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/fills"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "fills": [fill_json("trade-1", "order-1")],
+                "cursor": "stuck"
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let fills = tokio::time::timeout(
+            Duration::from_secs(5),
+            cb_client.list_all_fills(FillsQuery::new()),
+        )
+        .await
+        .expect("the fills stream should terminate")
+        .unwrap();
+        assert_eq!(fills.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_fills_stops_on_empty_page() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/fills"))
+            .and(query_param("cursor", "page-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "fills": [],
+                "cursor": "page-3"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/fills"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "fills": [fill_json("trade-1", "order-1")],
+                "cursor": "page-2"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let fills = cb_client.list_all_fills(FillsQuery::new()).await.unwrap();
+        assert_eq!(fills.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_order_with_fills() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/order-1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"order": order_json("order-1", "FILLED")})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/fills"))
+            .and(query_param("order_id", "order-1"))
+            .and(query_param("cursor", "page-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "fills": [fill_json("trade-2", "order-1")],
+                "cursor": ""
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/fills"))
+            .and(query_param("order_id", "order-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "fills": [fill_json("trade-1", "order-1")],
+                "cursor": "page-2"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let (order, fills) = cb_client.get_order_with_fills("order-1").await.unwrap();
+        assert_eq!(order.order_id, "order-1");
+        let trade_ids: Vec<&str> = fills.iter().map(|fill| fill.trade_id.as_str()).collect();
+        assert_eq!(trade_ids, vec!["trade-1", "trade-2"]);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_terminal() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/order-1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"order": order_json("order-1", "OPEN")})),
+            )
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/order-1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"order": order_json("order-1", "FILLED")})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let order = cb_client
+            .wait_for_terminal("order-1", Duration::from_millis(10), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(order.status, orders::Status::Filled);
+    }
+
+    #[tokio::test]
+    async fn test_verify_order_fill() {
+        let mut open_order = order_json("order-1", "OPEN");
+        open_order["time_in_force"] = "FILL_OR_KILL".into();
+        let mut filled_order = order_json("order-1", "FILLED");
+        filled_order["time_in_force"] = "FILL_OR_KILL".into();
+        let mut cancelled_order = order_json("order-2", "CANCELLED");
+        cancelled_order["time_in_force"] = "FILL_OR_KILL".into();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/order-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"order": open_order})),
+            )
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/order-1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"order": filled_order})),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/order-2"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"order": cancelled_order})),
+            )
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let interval = Duration::from_millis(10);
+        let timeout = Duration::from_secs(5);
+        let order = cb_client
+            .verify_order_fill("order-1", interval, timeout)
+            .await
+            .unwrap();
+        assert_eq!(order.status, orders::Status::Filled);
+
+        // Settled, but a fill-or-kill order should not be cancelled.
+        let result = cb_client
+            .verify_order_fill("order-2", interval, timeout)
+            .await;
+        assert!(matches!(result, Err(CbError::Other(_))));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_terminal_timeout() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/order-1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"order": order_json("order-1", "OPEN")})),
+            )
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let result = cb_client
+            .wait_for_terminal(
+                "order-1",
+                Duration::from_millis(20),
+                Duration::from_millis(50),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(CbError::NotSettled { status, .. }) if status == "OPEN"
+        ));
+        let requests = server.received_requests().await.unwrap().len();
+        assert!((2..=3).contains(&requests));
+    }
+
+    #[tokio::test]
+    async fn test_get_fill_by_trade_id() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/fills"))
+            .and(query_param("order_id", "order-1"))
+            .and(query_param("cursor", "page-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "fills": [fill_json("trade-3", "order-1")],
+                "cursor": ""
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/orders/historical/fills"))
+            .and(query_param("order_id", "order-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "fills": [fill_json("trade-1", "order-1"), fill_json("trade-2", "order-1")],
+                "cursor": "page-2"
+            })))
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let fill = cb_client.get_fill_by_trade_id("order-1", "trade-2").await;
+        assert_eq!(fill.unwrap().unwrap().trade_id, "trade-2");
+        // Found on the first page, the second one is not requested.
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+        let fill = cb_client.get_fill_by_trade_id("order-1", "trade-3").await;
+        assert_eq!(fill.unwrap().unwrap().trade_id, "trade-3");
+
+        let fill = cb_client.get_fill_by_trade_id("order-1", "trade-4").await;
+        assert!(fill.unwrap().is_none());
+    }
+
+    fn account_json(currency: &str) -> serde_json::Value {
+        serde_json::json!({
+            "uuid": Uuid::new_v4(),
+            "name": format!("{} Wallet", currency),
+            "currency": currency,
+            "available_balance": {"value": "1.5", "currency": currency},
+            "default": true,
+            "active": true,
+            "created_at": "2023-06-07T17:30:40.425Z",
+            "updated_at": null,
+            "deleted_at": null,
+            "type": "ACCOUNT_TYPE_CRYPTO",
+            "ready": true,
+            "hold": {"value": "0", "currency": currency}
+        })
+    }
+
+    #[tokio::test]
+    async fn test_get_account_by_uuid_str() {
+        let server = MockServer::start().await;
+        let account = account_json("BTC");
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/brokerage/accounts/{}",
+                account["uuid"].as_str().unwrap()
+            )))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"account": account})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let result = cb_client
+            .get_account_by_uuid_str(account["uuid"].as_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(result.currency, "BTC");
+
+        let result = cb_client.get_account_by_uuid_str("not-a-uuid").await;
+        assert!(matches!(result, Err(CbError::Other(_))));
+    }
+
+    #[tokio::test]
+    async fn test_get_account_by_currency() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/accounts"))
+            .and(query_param("cursor", "page-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "accounts": [account_json("BTC"), account_json("SOL")],
+                "has_next": false,
+                "cursor": "",
+                "size": 2
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/accounts"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "accounts": [account_json("USD"), account_json("ETH")],
+                "has_next": true,
+                "cursor": "page-2",
+                "size": 2
+            })))
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let account = cb_client.get_account_by_currency("sol").await.unwrap();
+        assert_eq!(account.unwrap().currency, "SOL");
+        let account = cb_client.get_account_by_currency("eth").await.unwrap();
+        assert_eq!(account.unwrap().currency, "ETH");
+        let account = cb_client.get_account_by_currency("DOGE").await.unwrap();
+        assert!(account.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_products_stream() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("offset", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "products": [product_json("A-USD"), product_json("B-USD")],
+                "num_products": 3
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param("offset", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "products": [product_json("C-USD")],
+                "num_products": 3
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let batches: Vec<Vec<Product>> = cb_client
+            .list_products_stream(Some(2), None, &None, None)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[1][0].product_id, "C-USD");
+    }
+
+    #[tokio::test]
+    async fn test_get_best_bid_ask_map() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/best_bid_ask"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "pricebooks": [
+                    {
+                        "product_id": "BTC-USD",
+                        "bids": [{"price": "30000", "size": "0.1"}],
+                        "asks": [{"price": "30001", "size": "0.2"}],
+                        "time": "2023-06-01T10:00:00Z"
+                    },
+                    {
+                        "product_id": "ETH-USD",
+                        "bids": [{"price": "1800", "size": "1"}],
+                        "asks": [],
+                        "time": "2023-06-01T10:00:00Z"
+                    }
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let pricebooks = cb_client
+            .get_best_bid_ask_map(&Some(vec!["BTC-USD", "ETH-USD"]))
+            .await
+            .unwrap();
+        assert_eq!(pricebooks.len(), 2);
+        assert_eq!(pricebooks["BTC-USD"].product_id, "BTC-USD");
+        assert_eq!(
+            pricebooks["ETH-USD"].best_bid().unwrap().price,
+            BigDecimal::from(1800)
+        );
+        assert!(pricebooks["ETH-USD"].best_ask().is_none());
+    }
+
+    #[test]
+    fn test_product_uri() {
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider);
+        assert_eq!(
+            cb_client.get_product_uri("BTC-USD", Some(true)),
+            MAIN_URL.to_string() + "/brokerage/products/BTC-USD?get_tradability_status=true"
+        );
+        assert_eq!(
+            cb_client.get_product_uri("BTC-USD", None),
+            MAIN_URL.to_string() + "/brokerage/products/BTC-USD"
+        );
+    }
+
+    #[test]
+    fn test_transactions_summary_uri() {
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider);
+        assert_eq!(
+            cb_client.get_transactions_summary_uri(
+                &TransactionsSummaryQuery::new()
+                    .product_type(ProductType::Spot)
+                    .product_id("BTC-USD")
+            ),
+            MAIN_URL.to_string()
+                + "/brokerage/transaction_summary?product_type=SPOT&product_id=BTC-USD"
+        );
+    }
+
+    #[test]
+    fn test_custom_base_url() {
+        let portfolio_uuid = Uuid::from_str("8a2ac7ea-3c84-4fb3-9e55-0b9c1e2f7a11").unwrap();
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url("http://localhost:8080/api/v3/");
+        assert_eq!(
+            cb_client.get_portfolio_uri(&portfolio_uuid),
+            "http://localhost:8080/api/v3/brokerage/portfolios/8a2ac7ea-3c84-4fb3-9e55-0b9c1e2f7a11"
+        );
+        assert_eq!(
+            cb_client.get_list_accounts_uri(Some(4), None),
+            "http://localhost:8080/api/v3/brokerage/accounts?limit=4"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_request_context() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/products/BTC-USD"))
+            .respond_with(ResponseTemplate::new(502).set_body_string("<html>Bad Gateway</html>"))
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        let err = cb_client.get_product("BTC-USD").await.unwrap_err();
+        match &err {
+            CbError::Serde {
+                context: Some(context),
+                ..
+            } => assert_eq!(context.status, 502),
+            other => panic!("unexpected error: {:?}", other),
+        }
+        let message = err.to_string();
+        assert!(message.contains("GET /brokerage/products/BTC-USD"));
+        assert!(message.contains("502"));
+    }
+
+    #[tokio::test]
+    async fn test_get_product_with_base_url() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/brokerage/products/BTC-USD"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "error": "NOT_FOUND",
+                "code": 5,
+                "message": "product not found",
+                "details": {"type_url": "", "value": 0}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider).with_base_url(&server.uri());
+        match cb_client.get_product("BTC-USD").await {
+            Err(CbError::Coinbase {
+                error: err,
+                context,
+            }) => {
+                assert_eq!(context.path, "/brokerage/products/BTC-USD");
+                assert_eq!(err.error, "NOT_FOUND");
+                assert_eq!(err.code, 5);
+                assert_eq!(err.message, "product not found");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_and_delete() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(body_json(serde_json::json!({"name": "Grid bot"})))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"ok": true}"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+            .mount(&server)
+            .await;
+
+        let provider = DummyTokenProvider;
+        let cb_client = CbClient::new(&provider);
+        let result: serde_json::Value = cb_client
+            .put(&server.uri(), &HashMap::from([("name", "Grid bot")]))
+            .await
+            .unwrap();
+        assert_eq!(result["ok"], true);
+        let result: serde_json::Value = cb_client.delete(&server.uri()).await.unwrap();
+        assert_eq!(result, serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_list_fills_uri() {
+        let query = orders::FillsQuery::new()
+            .order_id("0000-000000-000000")
+            .product_id("BTC-USD")
+            .start_sequence_timestamp(DateTime::from_str("2023-01-01T00:00:00Z").unwrap())
+            .end_sequence_timestamp(DateTime::from_str("2023-02-01T00:00:00Z").unwrap())
+            .limit(50)
+            .cursor("789");
+        let provider = DummyTokenProvider;
+        assert_eq!(
+            CbClient::new(&provider).get_list_fills_uri(&query),
+            MAIN_URL.to_string()
+                + "/brokerage/orders/historical/fills?order_id=0000-000000-000000&product_id=BTC-USD"
+                + "&start_sequence_timestamp=2023-01-01T00%3A00%3A00Z"
+                + "&end_sequence_timestamp=2023-02-01T00%3A00%3A00Z&limit=50&cursor=789"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Structures & Enums representing Coinbase's convert related structures
+//!
+//! Conversions happen between stable pairs, e.g. USD and USDC, in two steps: a quote is first
+//! requested, then the resulting trade is committed.
+
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use serde_derive::{Deserialize, Serialize};
+use serde_enum_str::{Deserialize_enum_str, Serialize_enum_str};
+
+use crate::accounts::Balance;
+use crate::orders::f64_to_valid_bigdecimal;
+
+/// Enum representing the possible status of a convert trade
+#[derive(Deserialize_enum_str, Serialize_enum_str, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConvertTradeStatus {
+    TradeStatusUnspecified,
+    TradeStatusCreated,
+    TradeStatusStarted,
+    TradeStatusCompleted,
+    TradeStatusCanceled,
+    /// Any value not known by this crate.
+    #[serde(other)]
+    Unknown(String),
+}
+
+/// Structure representing the fee charged for a conversion
+#[derive(Deserialize, Debug)]
+pub struct ConvertFee {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub amount: Balance,
+    #[serde(default)]
+    pub label: String,
+}
+
+/// Structure representing Coinbase's convert trade
+#[derive(Deserialize, Debug)]
+pub struct ConvertTrade {
+    /// Unique identifier of the trade, to pass when getting or committing it.
+    pub id: String,
+    pub status: ConvertTradeStatus,
+    /// Amount as requested by the user.
+    pub user_entered_amount: Balance,
+    /// Amount that will be converted.
+    pub amount: Balance,
+    /// Amount before fees.
+    pub subtotal: Balance,
+    pub total_fee: ConvertFee,
+}
+
+#[doc(hidden)]
+/// Structure representing Coinbase's wrapper response for a convert trade
+#[derive(Deserialize, Debug)]
+pub struct ConvertTradeResponse {
+    pub trade: ConvertTrade,
+}
+
+/// Structure to fill to request a convert quote, to be sent to CB
+#[derive(Serialize, Debug)]
+pub struct ConvertQuoteRequest {
+    from_account: String,
+    to_account: String,
+    amount: BigDecimal,
+}
+
+/// Structure to fill to commit a convert trade, to be sent to CB
+#[derive(Serialize, Debug)]
+pub struct ConvertTradeRequest {
+    pub(crate) from_account: String,
+    pub(crate) to_account: String,
+}
+
+/// Create a request for a quote converting `amount` from the `from_account` to the `to_account`.
+pub fn create_quote_request(
+    from_account: &str,
+    to_account: &str,
+    amount: f64,
+) -> Result<ConvertQuoteRequest> {
+    Ok(ConvertQuoteRequest {
+        from_account: from_account.to_string(),
+        to_account: to_account.to_string(),
+        amount: f64_to_valid_bigdecimal(amount)?,
+    })
+}
+
+//=========== TESTS ===========================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_convert_quote_deserialize() {
+        let input = r##"{
+            "trade": {
+                "id": "b2f5c1a8-6d3e-4f7a-9c0b-1e2d3f4a5b6c",
+                "status": "TRADE_STATUS_CREATED",
+                "user_entered_amount": {"value": "100", "currency": "USD"},
+                "amount": {"value": "100", "currency": "USD"},
+                "subtotal": {"value": "99.5", "currency": "USD"},
+                "total": {"value": "100", "currency": "USD"},
+                "fees": [],
+                "total_fee": {
+                    "title": "Fee",
+                    "description": "Conversion fee",
+                    "amount": {"value": "0.5", "currency": "USD"},
+                    "label": "Fee"
+                },
+                "source": {"type": "LEDGER_ACCOUNT"},
+                "target": {"type": "LEDGER_ACCOUNT"},
+                "unit_price": {}
+            }
+        }"##;
+        let result: ConvertTradeResponse = serde_json::from_slice(input.as_bytes()).unwrap();
+        let trade = result.trade;
+        assert_eq!(trade.id, "b2f5c1a8-6d3e-4f7a-9c0b-1e2d3f4a5b6c");
+        assert_eq!(trade.status, ConvertTradeStatus::TradeStatusCreated);
+        assert_eq!(trade.subtotal.value, BigDecimal::from_str("99.5").unwrap());
+        assert_eq!(
+            trade.total_fee.amount.value,
+            BigDecimal::from_str("0.5").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_convert_trade_status_unknown() {
+        let status: ConvertTradeStatus =
+            serde_json::from_str(r##""TRADE_STATUS_NEW_ONE""##).unwrap();
+        assert_eq!(
+            status,
+            ConvertTradeStatus::Unknown("TRADE_STATUS_NEW_ONE".to_string())
+        );
+    }
+
+    #[test]
+    fn test_convert_quote_request_serialize() {
+        let request = create_quote_request("from-uuid", "to-uuid", 100.0).unwrap();
+        let result = serde_json::to_value(&request).unwrap();
+        assert_eq!(result["from_account"], "from-uuid");
+        assert_eq!(result["to_account"], "to-uuid");
+        let amount = BigDecimal::from_str(result["amount"].as_str().unwrap()).unwrap();
+        assert_eq!(amount, BigDecimal::from(100));
+        assert!(create_quote_request("from-uuid", "to-uuid", f64::INFINITY).is_err());
+    }
+}
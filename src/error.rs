@@ -2,21 +2,25 @@
 use reqwest;
 use serde_derive::{Deserialize, Serialize};
 use std::fmt;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Structure to deserialize the details of Coinbase's API error responses
 #[derive(Serialize, Deserialize, Debug)]
 struct CbRequestErrorDetails {
     type_url: String,
-    value: u8,
+    value: i64,
 }
 
 /// Structure to deserialize Coinbase's API error responses
 #[derive(thiserror::Error, Serialize, Deserialize, Debug)]
 pub struct CbRequestError {
-    error: String,
-    code: i32,
-    message: String,
+    /// Error kind, e.g. `INVALID_ARGUMENT` or `PERMISSION_DENIED`.
+    pub error: String,
+    /// gRPC status code matching `error`.
+    pub code: i32,
+    /// Human readable description of the error.
+    pub message: String,
     details: CbRequestErrorDetails,
 }
 
@@ -26,13 +30,111 @@ impl fmt::Display for CbRequestError {
     }
 }
 
+/// Request whose response could not be used, to tell which of several calls failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestContext {
+    /// HTTP method of the request, e.g. `GET`.
+    pub method: String,
+    /// Endpoint path, without the query.
+    pub path: String,
+    /// HTTP status code of the response.
+    pub status: u16,
+}
+
+impl fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} (status {})", self.method, self.path, self.status)
+    }
+}
+
 /// Enum accounting for the different error types arising from the client
 #[derive(Debug, Error)]
 pub enum CbError {
     #[error("http error {0}")]
-    Http(#[from] reqwest::Error),
+    Http(#[source] reqwest::Error),
+    #[error("request timed out")]
+    Timeout,
+    #[error(
+        "invalid json{}: {source}",
+        .context.as_ref().map(|context| format!(" from {}", context)).unwrap_or_default()
+    )]
+    Serde {
+        #[source]
+        source: serde_json::Error,
+        /// Request that returned the invalid body, if the json came from the REST API.
+        context: Option<Box<RequestContext>>,
+    },
+    #[error("Coinbase error from {context}: {error}")]
+    Coinbase {
+        error: CbRequestError,
+        /// Request that Coinbase answered with this error.
+        context: Box<RequestContext>,
+    },
+    #[error("rate limited, retry after {retry_after:?}")]
+    RateLimited {
+        /// Delay requested by Coinbase before sending new requests, if given.
+        retry_after: Option<Duration>,
+    },
+    #[error("authentication error: {0}")]
+    Auth(String),
+    #[error("trading is disabled, enable it with `CbClient::allow_trading(true)`")]
+    TradingDisabled,
+    #[error("order {order_id} is not settled yet, its status is {status}")]
+    NotSettled {
+        order_id: String,
+        /// Last status of the order, e.g. `OPEN`.
+        status: String,
+    },
+    #[error("websocket error: {0}")]
+    WebSocket(String),
     #[error(transparent)]
-    Serde(#[from] serde_json::Error),
-    #[error("Coinbase: {0}")]
-    Coinbase(CbRequestError),
+    Other(#[from] anyhow::Error),
+}
+
+impl From<reqwest::Error> for CbError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            CbError::Timeout
+        } else {
+            CbError::Http(err)
+        }
+    }
+}
+
+impl From<serde_json::Error> for CbError {
+    fn from(source: serde_json::Error) -> Self {
+        CbError::Serde {
+            source,
+            context: None,
+        }
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for CbError {
+    fn from(err: tokio_tungstenite::tungstenite::Error) -> Self {
+        CbError::WebSocket(err.to_string())
+    }
+}
+
+//=========== TESTS ===========================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_error_large_detail_value() {
+        let input = r##"{
+            "error": "INVALID_ARGUMENT",
+            "code": 3,
+            "message": "invalid product_id",
+            "details": {
+                "type_url": "type.googleapis.com/coinbase.public_api.authorization.Error",
+                "value": 4294967296
+            }
+        }"##;
+        let result: CbRequestError = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(result.code, 3);
+        assert_eq!(result.details.value, 4294967296);
+    }
 }
@@ -1,11 +1,16 @@
 //! Structures & Enums representing Coinbase's fee structures
 
+use anyhow::{anyhow, Result};
 use bigdecimal::BigDecimal;
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 use serde_enum_str::{Deserialize_enum_str, Serialize_enum_str};
+use std::str::FromStr;
+
+use crate::products::{ContractExpiryType, ProductType};
+use crate::DateTime;
 
 /// Structure representing Coinbase's fee tier
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
 pub struct FeeTier {
     /// Pricing tier for user, determined by notional (USD) volume.
     /// usd_from, usd_to uses comma to separate thousands -- keep as String; serde to BiDecimal will
@@ -19,10 +24,27 @@ pub struct FeeTier {
     pub taker_fee_rate: BigDecimal,
     /// Maker fee rate, applied if the order creates liquidity.
     pub maker_fee_rate: BigDecimal,
+    /// Lower bound (inclusive) of the Advanced Only Pricing tier, if the user has one.
+    pub aop_from: Option<String>,
+    /// Upper bound (exclusive) of the Advanced Only Pricing tier, if the user has one.
+    pub aop_to: Option<String>,
+}
+
+impl FeeTier {
+    /// Lower bound of the pricing tier, parsed from `usd_from`.
+    pub fn usd_from_value(&self) -> Result<BigDecimal> {
+        parse_usd_amount(&self.usd_from)
+            .ok_or_else(|| anyhow!("Invalid fee tier lower bound: {:?}", self.usd_from))
+    }
+
+    /// Upper bound of the pricing tier, parsed from `usd_to`. `None` for the unbounded top tier.
+    pub fn usd_to_value(&self) -> Option<BigDecimal> {
+        parse_usd_amount(&self.usd_to)
+    }
 }
 
 /// Structure representing Coinbase's margin rate.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct MarginRate {
     /// String representation allows for unlimited precision.
     pub value: String,
@@ -37,14 +59,75 @@ pub enum GoodsAndServicesTaxType {
 }
 
 /// Structure representing Coinbase's good and services tax structure.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct GoodsAndServicesTax {
     pub rate: String,
     pub r#type: GoodsAndServicesTaxType,
 }
 
+/// Query parameters to get a transactions summary, see
+/// [get_transactions_summary_with_query()](`crate::client::CbClient::get_transactions_summary_with_query`).
+///
+/// All parameters are optional:
+/// ```
+/// # use coinbase_v3::fees::TransactionsSummaryQuery;
+/// let query = TransactionsSummaryQuery::new().product_id("BTC-USD");
+/// ```
+#[derive(Debug, Default)]
+pub struct TransactionsSummaryQuery {
+    pub(crate) start_date: Option<DateTime>,
+    pub(crate) end_date: Option<DateTime>,
+    pub(crate) user_native_currency: Option<String>,
+    pub(crate) product_type: Option<ProductType>,
+    pub(crate) contract_expiry_type: Option<ContractExpiryType>,
+    pub(crate) product_id: Option<String>,
+}
+
+impl TransactionsSummaryQuery {
+    /// Instantiate a query without any filter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only summarize transactions after this date.
+    pub fn start_date(mut self, start_date: DateTime) -> Self {
+        self.start_date = Some(start_date);
+        self
+    }
+
+    /// Only summarize transactions before this date.
+    pub fn end_date(mut self, end_date: DateTime) -> Self {
+        self.end_date = Some(end_date);
+        self
+    }
+
+    /// Currency in which to denote the summary, e.g. `USD`.
+    pub fn user_native_currency(mut self, user_native_currency: &str) -> Self {
+        self.user_native_currency = Some(user_native_currency.to_string());
+        self
+    }
+
+    /// Only summarize transactions for this type of product.
+    pub fn product_type(mut self, product_type: ProductType) -> Self {
+        self.product_type = Some(product_type);
+        self
+    }
+
+    /// Only summarize transactions for futures of this contract expiry type.
+    pub fn contract_expiry_type(mut self, contract_expiry_type: ContractExpiryType) -> Self {
+        self.contract_expiry_type = Some(contract_expiry_type);
+        self
+    }
+
+    /// Only summarize transactions for this product.
+    pub fn product_id(mut self, product_id: &str) -> Self {
+        self.product_id = Some(product_id.to_string());
+        self
+    }
+}
+
 /// Structure representing Coinbase's transaction summary, that is the fees according to the fee tier
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct TransactionsSummary {
     /// Total volume across assets, denoted in USD.
     pub total_volume: f64,
@@ -61,6 +144,98 @@ pub struct TransactionsSummary {
     pub coinbase_pro_volume: f64,
     /// Coinbase Pro fees across assets, denoted in USD.
     pub coinbase_pro_fees: f64,
+    /// Total balance across assets, denoted in USD.
+    pub total_balance: Option<String>,
+    /// Whether a promotional fee is applied.
+    pub has_promo_fee: Option<bool>,
+}
+
+impl TransactionsSummary {
+    /// `total_volume` as a [`BigDecimal`].
+    pub fn total_volume_value(&self) -> Result<BigDecimal> {
+        f64_to_decimal("total_volume", self.total_volume)
+    }
+
+    /// `total_fees` as a [`BigDecimal`].
+    pub fn total_fees_value(&self) -> Result<BigDecimal> {
+        f64_to_decimal("total_fees", self.total_fees)
+    }
+
+    /// `advanced_trade_only_volume` as a [`BigDecimal`].
+    pub fn advanced_trade_only_volume_value(&self) -> Result<BigDecimal> {
+        f64_to_decimal(
+            "advanced_trade_only_volume",
+            self.advanced_trade_only_volume,
+        )
+    }
+
+    /// `advanced_trade_only_fees` as a [`BigDecimal`].
+    pub fn advanced_trade_only_fees_value(&self) -> Result<BigDecimal> {
+        f64_to_decimal("advanced_trade_only_fees", self.advanced_trade_only_fees)
+    }
+
+    /// `coinbase_pro_volume` as a [`BigDecimal`].
+    pub fn coinbase_pro_volume_value(&self) -> Result<BigDecimal> {
+        f64_to_decimal("coinbase_pro_volume", self.coinbase_pro_volume)
+    }
+
+    /// `coinbase_pro_fees` as a [`BigDecimal`].
+    pub fn coinbase_pro_fees_value(&self) -> Result<BigDecimal> {
+        f64_to_decimal("coinbase_pro_fees", self.coinbase_pro_fees)
+    }
+
+    /// `(maker_fee_rate, taker_fee_rate)` currently applied, according to the fee tier.
+    pub fn effective_rates(&self) -> (BigDecimal, BigDecimal) {
+        (
+            self.fee_tier.maker_fee_rate.clone(),
+            self.fee_tier.taker_fee_rate.clone(),
+        )
+    }
+
+    /// Fee that would be charged for an order of `notional` value, at the maker rate if
+    /// `is_maker` (the order adds liquidity), at the taker rate otherwise.
+    pub fn estimated_fee(&self, notional: BigDecimal, is_maker: bool) -> BigDecimal {
+        let rate = if is_maker {
+            &self.fee_tier.maker_fee_rate
+        } else {
+            &self.fee_tier.taker_fee_rate
+        };
+        notional * rate
+    }
+
+    /// Notional volume (in USD) still needed to reach the next fee tier.
+    ///
+    /// Returns `None` if the current tier has no upper bound (empty `usd_to`), i.e. it is the top
+    /// tier. Returns zero if the total volume already exceeds the upper bound of the current tier.
+    ///
+    /// Fails if `usd_to` is neither empty nor a valid amount.
+    pub fn volume_to_next_tier(&self) -> Result<Option<BigDecimal>> {
+        if self.fee_tier.usd_to.trim().is_empty() {
+            return Ok(None);
+        }
+        let usd_to = parse_usd_amount(&self.fee_tier.usd_to)
+            .ok_or_else(|| anyhow!("Invalid fee tier upper bound: {:?}", self.fee_tier.usd_to))?;
+        let remaining = usd_to - self.total_volume_value()?;
+        if remaining > BigDecimal::from(0) {
+            Ok(Some(remaining))
+        } else {
+            Ok(Some(BigDecimal::from(0)))
+        }
+    }
+}
+
+/// Convert `value` through its shortest decimal representation, e.g. `0.1` and not
+/// `0.1000000000000000055511151231257827`, as it was most likely written by Coinbase.
+fn f64_to_decimal(name: &str, value: f64) -> Result<BigDecimal> {
+    BigDecimal::from_str(&value.to_string())
+        .map_err(|err| anyhow!("Invalid {} {}: {}", name, value, err))
+}
+
+/// Parse a USD amount using commas as thousands separator, e.g. "10,000".
+///
+/// Returns `None` for an empty or invalid amount.
+fn parse_usd_amount(amount: &str) -> Option<BigDecimal> {
+    BigDecimal::from_str(&amount.replace(',', "")).ok()
 }
 
 //=========== TESTS ===========================================================
@@ -89,8 +264,57 @@ mod tests {
         );
     }
 
+    const TRANSACTION_SUMMARY: &str = r##"{
+            "total_volume": 1000,
+            "total_fees": 25,
+            "fee_tier": {
+                "pricing_tier": "<$10k",
+                "usd_from": "0",
+                "usd_to": "10,000",
+                "taker_fee_rate": "0.0010",
+                "maker_fee_rate": "0.0020"
+            },
+            "margin_rate": {
+                "value": "string"
+            },
+            "goods_and_services_tax": {
+                "rate": "string",
+                "type": "INCLUSIVE"
+            },
+            "advanced_trade_only_volume": 1000,
+            "advanced_trade_only_fees": 25,
+            "coinbase_pro_volume": 1000,
+            "coinbase_pro_fees": 25
+        }"##;
+
     #[test]
     fn test_transaction_summary_deserialize() {
+        let result: TransactionsSummary =
+            serde_json::from_slice(TRANSACTION_SUMMARY.as_bytes()).unwrap();
+        assert_eq!(result.total_volume, 1000.0);
+    }
+
+    #[test]
+    fn test_transaction_summary_decimal_values() {
+        let mut result: TransactionsSummary =
+            serde_json::from_slice(TRANSACTION_SUMMARY.as_bytes()).unwrap();
+        result.total_fees = 0.1;
+        assert_eq!(result.total_volume_value().unwrap(), BigDecimal::from(1000));
+        assert_eq!(
+            result.total_fees_value().unwrap(),
+            BigDecimal::from_str("0.1").unwrap()
+        );
+        assert_eq!(
+            result.coinbase_pro_fees_value().unwrap(),
+            BigDecimal::from(25)
+        );
+
+        result.total_volume = f64::NAN;
+        assert!(result.total_volume_value().is_err());
+    }
+
+    #[test]
+    fn test_full_transaction_summary_deserialize() {
         let input = r##"{
             "total_volume": 1000,
             "total_fees": 25,
@@ -99,7 +323,9 @@ mod tests {
                 "usd_from": "0",
                 "usd_to": "10,000",
                 "taker_fee_rate": "0.0010",
-                "maker_fee_rate": "0.0020"
+                "maker_fee_rate": "0.0020",
+                "aop_from": "0",
+                "aop_to": "50,000"
             },
             "margin_rate": {
                 "value": "string"
@@ -111,9 +337,84 @@ mod tests {
             "advanced_trade_only_volume": 1000,
             "advanced_trade_only_fees": 25,
             "coinbase_pro_volume": 1000,
-            "coinbase_pro_fees": 25
+            "coinbase_pro_fees": 25,
+            "total_balance": "1234.56",
+            "has_promo_fee": false
         }"##;
         let result: TransactionsSummary = serde_json::from_slice(input.as_bytes()).unwrap();
-        assert_eq!(result.total_volume, 1000.0);
+        assert_eq!(result.fee_tier.aop_from, Some("0".to_string()));
+        assert_eq!(result.fee_tier.aop_to, Some("50,000".to_string()));
+        assert_eq!(result.total_balance, Some("1234.56".to_string()));
+        assert_eq!(result.has_promo_fee, Some(false));
+
+        let result: TransactionsSummary =
+            serde_json::from_slice(TRANSACTION_SUMMARY.as_bytes()).unwrap();
+        assert_eq!(result.fee_tier.aop_from, None);
+        assert_eq!(result.total_balance, None);
+    }
+
+    #[test]
+    fn test_volume_to_next_tier() {
+        let mut result: TransactionsSummary =
+            serde_json::from_slice(TRANSACTION_SUMMARY.as_bytes()).unwrap();
+        assert_eq!(
+            result.volume_to_next_tier().unwrap(),
+            Some(BigDecimal::from(9000))
+        );
+
+        result.total_volume = 9999.9;
+        assert_eq!(
+            result.volume_to_next_tier().unwrap(),
+            Some(BigDecimal::from_str("0.1").unwrap())
+        );
+
+        result.total_volume = 12000.0;
+        assert_eq!(
+            result.volume_to_next_tier().unwrap(),
+            Some(BigDecimal::from(0))
+        );
+
+        result.fee_tier.usd_to = "".to_string();
+        assert_eq!(result.volume_to_next_tier().unwrap(), None);
+
+        result.fee_tier.usd_to = "n/a".to_string();
+        assert!(result.volume_to_next_tier().is_err());
+    }
+
+    #[test]
+    fn test_effective_rates_and_estimated_fee() {
+        let result: TransactionsSummary =
+            serde_json::from_slice(TRANSACTION_SUMMARY.as_bytes()).unwrap();
+        let (maker_fee_rate, taker_fee_rate) = result.effective_rates();
+        assert_eq!(maker_fee_rate, BigDecimal::from_str("0.0020").unwrap());
+        assert_eq!(taker_fee_rate, BigDecimal::from_str("0.0010").unwrap());
+
+        let notional = BigDecimal::from(5000);
+        assert_eq!(
+            result.estimated_fee(notional.clone(), true),
+            BigDecimal::from(10)
+        );
+        assert_eq!(result.estimated_fee(notional, false), BigDecimal::from(5));
+    }
+
+    #[test]
+    fn test_fee_tier_bounds() {
+        let mut fee_tier = FeeTier {
+            pricing_tier: "$10k-$50k".to_string(),
+            usd_from: "10,000".to_string(),
+            usd_to: "50,000".to_string(),
+            taker_fee_rate: BigDecimal::from_str("0.004").unwrap(),
+            maker_fee_rate: BigDecimal::from_str("0.0025").unwrap(),
+            aop_from: None,
+            aop_to: None,
+        };
+        assert_eq!(fee_tier.usd_from_value().unwrap(), BigDecimal::from(10000));
+        assert_eq!(fee_tier.usd_to_value(), Some(BigDecimal::from(50000)));
+
+        fee_tier.usd_to = "".to_string();
+        assert_eq!(fee_tier.usd_to_value(), None);
+
+        fee_tier.usd_from = "".to_string();
+        assert!(fee_tier.usd_from_value().is_err());
     }
 }
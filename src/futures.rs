@@ -0,0 +1,237 @@
+//! Structures & Enums representing Coinbase's futures (CFM) related structures
+
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Deserializer};
+use serde_derive::Serialize;
+
+use crate::accounts::Balance;
+use crate::orders::f64_to_valid_bigdecimal;
+use crate::products::Side;
+use crate::DateTime;
+
+/// Structure representing the balance summary of a futures account
+///
+/// Amounts are in USD.
+#[derive(Deserialize, Debug)]
+pub struct FuturesBalanceSummary {
+    /// Amount available to open new futures positions.
+    pub futures_buying_power: Balance,
+    /// Sum of the spot (CBI) and futures (CFM) USD balances.
+    pub total_usd_balance: Balance,
+    /// USD balance of the spot account.
+    pub cbi_usd_balance: Balance,
+    /// USD balance of the futures account.
+    pub cfm_usd_balance: Balance,
+    pub total_open_orders_hold_amount: Balance,
+    pub unrealized_pnl: Balance,
+    pub daily_realized_pnl: Balance,
+    /// Margin required to hold the open positions.
+    pub initial_margin: Balance,
+    pub available_margin: Balance,
+    /// Balance below which the positions get liquidated.
+    pub liquidation_threshold: Balance,
+    /// Distance to the liquidation threshold.
+    pub liquidation_buffer_amount: Balance,
+    /// Distance to the liquidation threshold, in percent.
+    pub liquidation_buffer_percentage: BigDecimal,
+}
+
+#[doc(hidden)]
+/// Structure representing Coinbase's wrapper response for the futures balance summary
+#[derive(Deserialize, Debug)]
+pub struct FuturesBalanceSummaryResponse {
+    pub balance_summary: FuturesBalanceSummary,
+}
+
+/// Structure representing an open futures position
+#[derive(Deserialize, Debug)]
+pub struct FuturesPosition {
+    /// The futures product, e.g. BIT-28JUL23-CDE.
+    pub product_id: String,
+    pub expiration_time: DateTime,
+    /// `Buy` for a long position, `Sell` for a short one.
+    #[serde(deserialize_with = "deserialize_position_side")]
+    pub side: Side,
+    pub number_of_contracts: BigDecimal,
+    pub current_price: BigDecimal,
+    pub avg_entry_price: BigDecimal,
+    pub unrealized_pnl: BigDecimal,
+    pub daily_realized_pnl: BigDecimal,
+}
+
+#[doc(hidden)]
+/// Structure representing Coinbase's wrapper response for multiple futures positions
+#[derive(Deserialize, Debug)]
+pub struct FuturesPositionsResponse {
+    pub positions: Vec<FuturesPosition>,
+}
+
+#[doc(hidden)]
+/// Structure representing Coinbase's wrapper response for a single futures position
+#[derive(Deserialize, Debug)]
+pub struct FuturesPositionResponse {
+    pub position: FuturesPosition,
+}
+
+/// Structure to fill to close a futures position, to be sent to CB
+#[derive(Serialize, Debug)]
+pub struct ClosePosition {
+    client_order_id: String,
+    product_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<BigDecimal>,
+}
+
+/// Create a request closing `size` contracts of the position on `product_id`.
+///
+/// The whole position is closed when `size` is `None`.
+pub fn create_close_position(product_id: &str, size: Option<f64>) -> Result<ClosePosition> {
+    let size = match size {
+        Some(size) => Some(f64_to_valid_bigdecimal(size)?),
+        None => None,
+    };
+    Ok(ClosePosition {
+        client_order_id: uuid::Uuid::new_v4().to_string(),
+        product_id: product_id.to_string(),
+        size,
+    })
+}
+
+// Positions are described as LONG or SHORT rather than with the order sides.
+fn deserialize_position_side<'de, D>(deserializer: D) -> Result<Side, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    let side = match value.as_str() {
+        "LONG" => Side::Buy,
+        "SHORT" => Side::Sell,
+        other => other.parse().map_err(serde::de::Error::custom)?,
+    };
+    Ok(side)
+}
+
+//=========== TESTS ===========================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_futures_balance_summary_deserialize() {
+        let input = r##"{
+            "balance_summary": {
+                "futures_buying_power": {"value": "1000", "currency": "USD"},
+                "total_usd_balance": {"value": "1500.5", "currency": "USD"},
+                "cbi_usd_balance": {"value": "500.5", "currency": "USD"},
+                "cfm_usd_balance": {"value": "1000", "currency": "USD"},
+                "total_open_orders_hold_amount": {"value": "0", "currency": "USD"},
+                "unrealized_pnl": {"value": "-12.34", "currency": "USD"},
+                "daily_realized_pnl": {"value": "5", "currency": "USD"},
+                "initial_margin": {"value": "200", "currency": "USD"},
+                "available_margin": {"value": "800", "currency": "USD"},
+                "liquidation_threshold": {"value": "100", "currency": "USD"},
+                "liquidation_buffer_amount": {"value": "900", "currency": "USD"},
+                "liquidation_buffer_percentage": "900",
+                "intraday_margin_window_measure": {
+                    "margin_window_type": "FCM_MARGIN_WINDOW_TYPE_INTRADAY",
+                    "margin_level": "MARGIN_LEVEL_TYPE_BASE",
+                    "initial_margin": "200",
+                    "maintenance_margin": "100",
+                    "liquidation_buffer_percentage": "900",
+                    "total_hold": "0",
+                    "futures_buying_power": "1000"
+                }
+            }
+        }"##;
+        let result: FuturesBalanceSummaryResponse =
+            serde_json::from_slice(input.as_bytes()).unwrap();
+        let summary = result.balance_summary;
+        assert_eq!(
+            summary.total_usd_balance.value,
+            BigDecimal::from_str("1500.5").unwrap()
+        );
+        assert_eq!(
+            summary.unrealized_pnl.value,
+            BigDecimal::from_str("-12.34").unwrap()
+        );
+        assert_eq!(summary.liquidation_buffer_percentage, BigDecimal::from(900));
+        assert_eq!(summary.futures_buying_power.currency, "USD");
+    }
+
+    #[test]
+    fn test_futures_positions_deserialize() {
+        let input = r##"{
+            "positions": [
+                {
+                    "product_id": "BIT-28JUL23-CDE",
+                    "expiration_time": "2023-07-28T15:00:00Z",
+                    "side": "LONG",
+                    "number_of_contracts": "2",
+                    "current_price": "29500",
+                    "avg_entry_price": "29000",
+                    "unrealized_pnl": "10",
+                    "daily_realized_pnl": "0"
+                },
+                {
+                    "product_id": "ET-28JUL23-CDE",
+                    "expiration_time": "2023-07-28T15:00:00Z",
+                    "side": "SHORT",
+                    "number_of_contracts": "1",
+                    "current_price": "1850.5",
+                    "avg_entry_price": "1900",
+                    "unrealized_pnl": "4.95",
+                    "daily_realized_pnl": "-1.2"
+                }
+            ]
+        }"##;
+        let result: FuturesPositionsResponse = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(result.positions.len(), 2);
+        assert_eq!(result.positions[0].side, Side::Buy);
+        assert_eq!(result.positions[1].side, Side::Sell);
+        assert_eq!(
+            result.positions[1].current_price,
+            BigDecimal::from_str("1850.5").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_futures_position_deserialize() {
+        let input = r##"{
+            "position": {
+                "product_id": "BIT-28JUL23-CDE",
+                "expiration_time": "2023-07-28T15:00:00Z",
+                "side": "SELL",
+                "number_of_contracts": "3",
+                "current_price": "29500",
+                "avg_entry_price": "29000",
+                "unrealized_pnl": "-15",
+                "daily_realized_pnl": "0"
+            }
+        }"##;
+        let result: FuturesPositionResponse = serde_json::from_slice(input.as_bytes()).unwrap();
+        let position = result.position;
+        assert_eq!(position.product_id, "BIT-28JUL23-CDE");
+        assert_eq!(position.side, Side::Sell);
+        assert_eq!(position.number_of_contracts, BigDecimal::from(3));
+        assert_eq!(position.expiration_time.timestamp(), 1690556400);
+    }
+
+    #[test]
+    fn test_close_position_serialize() {
+        let close_position = create_close_position("BIT-28JUL23-CDE", Some(2.0)).unwrap();
+        let result = serde_json::to_value(&close_position).unwrap();
+        assert_eq!(result["product_id"], "BIT-28JUL23-CDE");
+        assert!(result["client_order_id"].is_string());
+        let size = BigDecimal::from_str(result["size"].as_str().unwrap()).unwrap();
+        assert_eq!(size, BigDecimal::from(2));
+
+        let close_position = create_close_position("BIT-28JUL23-CDE", None).unwrap();
+        let result = serde_json::to_value(&close_position).unwrap();
+        assert!(result.get("size").is_none());
+
+        assert!(create_close_position("BIT-28JUL23-CDE", Some(f64::NAN)).is_err());
+    }
+}
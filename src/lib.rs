@@ -8,20 +8,42 @@
 //!
 //! In addition this crates provides:
 //!   - A client based on [reqwest](https://docs.rs/reqwest/latest/reqwest/)
-//!   expecting a Oauth2 token provider.
+//!     expecting a Oauth2 token provider.
 //!   - A basic OAuth2 token provider based on [oauth2](https://docs.rs/oauth2/4.4.1/oauth2/).
+//!   - A JWT token provider for Coinbase Developer Platform (CDP) API keys, see [`jwt_auth`].
+//!   - A WebSocket client for the real-time market data feeds, see [`ws`].
+//!   - A [`api::BrokerageApi`] trait over the client, to test code using it with a fake.
 //!
 //! Notes:
 //!   - The OAuth2 token provider is basic and it may be replaced
-//!   by a fancier one implementing the [`basic_oauth::AccessTokenProvider`] trait.
+//!     by a fancier one implementing the [`basic_oauth::AccessTokenProvider`] trait.
 //!   - In particular, it is not taking care of the Access Token by periodically
-//!   sending the Refresh Token to the server.
+//!     sending the Refresh Token to the server.
 //!
 //!  ## Warning
 //!
 //! Use these bindings at your own risk. You may want to review the source code
 //! before.
 //!
+//! ## Cargo features
+//!
+//! The TLS backend used by the REST client, the OAuth2 provider and the WebSocket client is
+//! chosen with one of these features:
+//!   - `native-tls` (default): the platform's TLS library, e.g. OpenSSL on Linux.
+//!   - `rustls-tls`: [rustls](https://docs.rs/rustls/latest/rustls/) with the Mozilla root
+//!     certificates, handy for static musl builds and cross-compilation.
+//!
+//! To use rustls, disable the default features:
+//!
+//! ```toml
+//! coinbase-v3 = { version = "0.1", default-features = false, features = ["rustls-tls"] }
+//! ```
+//!
+//! The optional `tracing` feature instruments every API call with a
+//! [tracing](https://docs.rs/tracing/latest/tracing/) span recording the method, the endpoint
+//! path, the status code and the elapsed time. Response bodies are only logged at the `TRACE`
+//! level.
+//!
 //! # Usage
 //!
 //! Most if not all API calls have examples attached to them. They can be found
@@ -40,9 +62,12 @@
 //!     // Set up the Oauth2 Client -- can be a different one
 //!     let (client_id, client_secret, redirect_url) = utils::get_env_variables();
 //!     let oauth_cb_client = basic_oauth::OAuthCbClient::new(&client_id, &client_secret, &redirect_url)
+//!         .unwrap()
 //!         .add_scope("wallet:user:read")
+//!         .unwrap()
 //!         .authorize_once()
-//!         .await;
+//!         .await
+//!         .unwrap();
 //!
 //!     // Create the client
 //!     let cb_client = client::CbClient::new(&oauth_cb_client);
@@ -56,7 +81,7 @@
 //!
 //!     // You may want to revoke the token access for increased security
 //!     // by default it should have a lifetime of 2 hours.
-//!     oauth_cb_client.revoke_access().await;
+//!     oauth_cb_client.close().await.unwrap();
 //! }
 //!```
 //!
@@ -73,9 +98,12 @@
 //!     // Same as above
 //!     let (client_id, client_secret, redirect_url) = utils::get_env_variables();
 //!     let oauth_cb_client = basic_oauth::OAuthCbClient::new(&client_id, &client_secret, &redirect_url)
+//!         .unwrap()
 //!         .add_scope("wallet:accounts:read")
+//!         .unwrap()
 //!         .authorize_once()
-//!         .await;
+//!         .await
+//!         .unwrap();
 //!     let cb_client = client::CbClient::new(&oauth_cb_client);
 //!
 //!     // Request to list accounts
@@ -102,14 +130,21 @@
 
 // ================ Libary modules ============================================
 pub mod accounts;
+pub mod api;
 pub mod basic_oauth;
 pub mod client;
+pub mod convert;
 pub mod error;
 pub mod fees;
+pub mod futures;
+pub mod jwt_auth;
 pub mod orders;
+pub mod portfolios;
 pub mod products;
 pub mod scopes;
+pub mod time;
 pub mod utils;
+pub mod ws;
 
 // ================ Libary wide variables =====================================
 /// Base URL for Coinbase's v3 API.
@@ -5,9 +5,10 @@ use anyhow::Result;
 use bigdecimal::{BigDecimal, FromPrimitive};
 use serde_derive::{Deserialize, Serialize};
 use serde_enum_str::{Deserialize_enum_str, Serialize_enum_str};
+use std::str::FromStr;
 
-use crate::products::ProductType;
 use crate::products::Side; // Move to order? might make more sense...
+use crate::products::{ContractExpiryType, ProductType};
 use crate::DateTime;
 
 /// Structure representing Coinbase's order configuration structure
@@ -17,10 +18,153 @@ use crate::DateTime;
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq)]
 pub struct OrderConfiguration {
     pub market_market_ioc: Option<Market>,
+    pub sor_limit_ioc: Option<SorLimitIoc>,
     pub limit_limit_gtc: Option<Limit>,
     pub limit_limit_gtd: Option<Limit>,
     pub stop_limit_stop_limit_gtc: Option<StopLimit>,
     pub stop_limit_stop_limit_gtd: Option<StopLimit>,
+    pub trigger_bracket_gtc: Option<TriggerBracket>,
+    pub trigger_bracket_gtd: Option<TriggerBracket>,
+}
+
+impl OrderConfiguration {
+    /// Start building an [`OrderConfiguration`] holding exactly one configuration.
+    ///
+    /// ```
+    /// # use coinbase_v3::orders::{Market, OrderConfiguration};
+    /// let order_configuration = OrderConfiguration::builder()
+    ///     .market_market_ioc(Market {
+    ///         quote_size: Some(10.into()),
+    ///         base_size: None,
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder() -> OrderConfigurationBuilder {
+        OrderConfigurationBuilder::default()
+    }
+
+    /// Check that exactly one configuration is set, as expected by Coinbase.
+    fn validate(&self) -> Result<()> {
+        let set_configurations: Vec<&str> = [
+            ("market_market_ioc", self.market_market_ioc.is_some()),
+            ("sor_limit_ioc", self.sor_limit_ioc.is_some()),
+            ("limit_limit_gtc", self.limit_limit_gtc.is_some()),
+            ("limit_limit_gtd", self.limit_limit_gtd.is_some()),
+            (
+                "stop_limit_stop_limit_gtc",
+                self.stop_limit_stop_limit_gtc.is_some(),
+            ),
+            (
+                "stop_limit_stop_limit_gtd",
+                self.stop_limit_stop_limit_gtd.is_some(),
+            ),
+            ("trigger_bracket_gtc", self.trigger_bracket_gtc.is_some()),
+            ("trigger_bracket_gtd", self.trigger_bracket_gtd.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, is_set)| *is_set)
+        .map(|(name, _)| name)
+        .collect();
+
+        anyhow::ensure!(
+            set_configurations.len() == 1,
+            "Exactly one order configuration should be set. Got: {:?}",
+            set_configurations
+        );
+        if let Some(market) = &self.market_market_ioc {
+            anyhow::ensure!(
+                market.quote_size.is_some() != market.base_size.is_some(),
+                "Exactly one of quote_size and base_size should be set for a market order"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Builder for an [`OrderConfiguration`]
+///
+/// Coinbase expects exactly one configuration per order. It is checked when calling
+/// [`build()`](`OrderConfigurationBuilder::build`), which is convenient when the configuration
+/// comes from loosely typed input such as a web form.
+#[derive(Debug, Default)]
+pub struct OrderConfigurationBuilder {
+    market_market_ioc: Option<Market>,
+    sor_limit_ioc: Option<SorLimitIoc>,
+    limit_limit_gtc: Option<Limit>,
+    limit_limit_gtd: Option<Limit>,
+    stop_limit_stop_limit_gtc: Option<StopLimit>,
+    stop_limit_stop_limit_gtd: Option<StopLimit>,
+    trigger_bracket_gtc: Option<TriggerBracket>,
+    trigger_bracket_gtd: Option<TriggerBracket>,
+}
+
+impl OrderConfigurationBuilder {
+    /// Set a market Immediate-Or-Cancel configuration.
+    pub fn market_market_ioc(mut self, market: Market) -> Self {
+        self.market_market_ioc = Some(market);
+        self
+    }
+
+    /// Set a smart-order-routing limit Immediate-Or-Cancel configuration.
+    pub fn sor_limit_ioc(mut self, sor_limit_ioc: SorLimitIoc) -> Self {
+        self.sor_limit_ioc = Some(sor_limit_ioc);
+        self
+    }
+
+    /// Set a limit Good-Til-Canceled configuration.
+    pub fn limit_limit_gtc(mut self, limit: Limit) -> Self {
+        self.limit_limit_gtc = Some(limit);
+        self
+    }
+
+    /// Set a limit Good-Til-Date configuration.
+    pub fn limit_limit_gtd(mut self, limit: Limit) -> Self {
+        self.limit_limit_gtd = Some(limit);
+        self
+    }
+
+    /// Set a stop-limit Good-Til-Canceled configuration.
+    pub fn stop_limit_stop_limit_gtc(mut self, stop_limit: StopLimit) -> Self {
+        self.stop_limit_stop_limit_gtc = Some(stop_limit);
+        self
+    }
+
+    /// Set a stop-limit Good-Til-Date configuration.
+    pub fn stop_limit_stop_limit_gtd(mut self, stop_limit: StopLimit) -> Self {
+        self.stop_limit_stop_limit_gtd = Some(stop_limit);
+        self
+    }
+
+    /// Set a trigger-bracket Good-Til-Canceled configuration.
+    pub fn trigger_bracket_gtc(mut self, trigger_bracket: TriggerBracket) -> Self {
+        self.trigger_bracket_gtc = Some(trigger_bracket);
+        self
+    }
+
+    /// Set a trigger-bracket Good-Til-Date configuration.
+    pub fn trigger_bracket_gtd(mut self, trigger_bracket: TriggerBracket) -> Self {
+        self.trigger_bracket_gtd = Some(trigger_bracket);
+        self
+    }
+
+    /// Build the [`OrderConfiguration`].
+    ///
+    /// Fails if no configuration, or more than one, has been set.
+    pub fn build(self) -> Result<OrderConfiguration> {
+        let order_configuration = OrderConfiguration {
+            market_market_ioc: self.market_market_ioc,
+            sor_limit_ioc: self.sor_limit_ioc,
+            limit_limit_gtc: self.limit_limit_gtc,
+            limit_limit_gtd: self.limit_limit_gtd,
+            stop_limit_stop_limit_gtc: self.stop_limit_stop_limit_gtc,
+            stop_limit_stop_limit_gtd: self.stop_limit_stop_limit_gtd,
+            trigger_bracket_gtc: self.trigger_bracket_gtc,
+            trigger_bracket_gtd: self.trigger_bracket_gtd,
+        };
+        order_configuration.validate()?;
+        Ok(order_configuration)
+    }
 }
 
 /// Structure representing Coinbase's Market order structure
@@ -32,6 +176,18 @@ pub struct Market {
     pub base_size: Option<BigDecimal>,
 }
 
+/// Structure representing Coinbase's smart-order-routing limit Immediate-Or-Cancel structure
+///
+/// The order is routed across venues for the best price, up to `limit_price`, and whatever is
+/// not filled right away is cancelled.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq)]
+pub struct SorLimitIoc {
+    /// Amount of base currency to spend on order
+    pub base_size: BigDecimal,
+    /// Ceiling price for which the order should get filled
+    pub limit_price: BigDecimal,
+}
+
 /// Structure representing Coinbase's limit order structure
 ///
 /// end_time is only used for gtd orders, not gtc
@@ -73,6 +229,24 @@ pub struct StopLimit {
     pub end_time: Option<DateTime>,
 }
 
+/// Structure representing Coinbase's trigger-bracket order structure
+///
+/// A limit order paired with a stop-loss: once filled at `limit_price`, the position is
+/// closed if the last trade price reaches `stop_trigger_price`.
+///
+/// end_time is only used for gtd orders, not gtc
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq)]
+pub struct TriggerBracket {
+    /// Amount of base currency to spend on order
+    pub base_size: BigDecimal,
+    /// Ceiling price for which the order should get filled
+    pub limit_price: BigDecimal,
+    /// Price at which the stop-loss leg of the bracket should trigger
+    pub stop_trigger_price: BigDecimal,
+    /// Time at which the order should be cancelled if it's not filled.
+    pub end_time: Option<DateTime>,
+}
+
 /// Enum representing the possible status values of an order
 #[derive(Deserialize_enum_str, Serialize_enum_str, Debug, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -83,6 +257,21 @@ pub enum Status {
     Expired,
     Failed,
     UnknownOrderStatus,
+    /// Any value not known by this crate.
+    #[serde(other)]
+    Unknown(String),
+}
+
+impl Status {
+    /// Whether the order has reached a final state: filled, cancelled, expired or failed.
+    ///
+    /// Unknown statuses are not considered terminal.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Status::Filled | Status::Cancelled | Status::Expired | Status::Failed
+        )
+    }
 }
 
 /// Enum representing the possible values for the time in force of an order
@@ -104,6 +293,9 @@ pub enum TriggerStatus {
     InvalidOrderType,
     StopPending,
     StopTriggered,
+    /// Any value not known by this crate.
+    #[serde(other)]
+    Unknown(String),
 }
 
 /// Enum representing the possible values for type of order
@@ -116,6 +308,9 @@ pub enum OrderType {
     Stop,
     #[serde(rename = "STOP_LIMIT")]
     StopLimitOrderType,
+    /// Any value not known by this crate.
+    #[serde(other)]
+    Unknown(String),
 }
 
 /// Enum representing the possible values for the reject reason
@@ -134,7 +329,7 @@ pub enum OrderPlacementSource {
 }
 
 /// Structure representing an order response
-#[derive(Deserialize, Debug, Eq, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq)]
 pub struct Order {
     /// The unique id for this order
     pub order_id: String,
@@ -176,11 +371,15 @@ pub struct Order {
     /// derived field: filled_value + total_fees for buy orders and filled_value - total_fees for sell orders.
     pub total_value_after_fees: String,
     /// Possible values: [UNKNOWN_TRIGGER_STATUS, INVALID_ORDER_TYPE, STOP_PENDING, STOP_TRIGGERED]
-    pub trigger_status: TriggerStatus,
+    ///
+    /// Not always sent, e.g. for orders without a stop.
+    pub trigger_status: Option<TriggerStatus>,
     /// Possible values: [UNKNOWN_ORDER_TYPE, MARKET, LIMIT, STOP, STOP_LIMIT]
     pub order_type: OrderType,
     /// Possible values: REJECT_REASON_UNSPECIFIED
-    pub reject_reason: RejectReason,
+    ///
+    /// Not always sent, e.g. for orders that were not rejected.
+    pub reject_reason: Option<RejectReason>,
     // True if the order is fully filled, false otherwise.
     pub settled: bool,
     /// Possible values: [SPOT, FUTURE]
@@ -197,6 +396,52 @@ pub struct Order {
     pub is_liquidation: bool,
 }
 
+/// Parsed accessors for the numeric fields Coinbase sends as strings.
+///
+/// They fail on empty or malformed values, which Coinbase sometimes sends, e.g. `""` for the
+/// average filled price of an order without fills.
+impl Order {
+    /// [`Order::filled_size`] as a `BigDecimal`.
+    pub fn filled_size(&self) -> Result<BigDecimal> {
+        parse_decimal_field("filled_size", &self.filled_size)
+    }
+
+    /// [`Order::average_filled_price`] as a `BigDecimal`.
+    pub fn average_filled_price(&self) -> Result<BigDecimal> {
+        parse_decimal_field("average_filled_price", &self.average_filled_price)
+    }
+
+    /// [`Order::fee`] as a `BigDecimal`.
+    pub fn fee(&self) -> Result<BigDecimal> {
+        parse_decimal_field("fee", &self.fee)
+    }
+
+    /// [`Order::filled_value`] as a `BigDecimal`.
+    pub fn filled_value(&self) -> Result<BigDecimal> {
+        parse_decimal_field("filled_value", &self.filled_value)
+    }
+
+    /// [`Order::total_fees`] as a `BigDecimal`.
+    pub fn total_fees(&self) -> Result<BigDecimal> {
+        parse_decimal_field("total_fees", &self.total_fees)
+    }
+
+    /// [`Order::total_value_after_fees`] as a `BigDecimal`.
+    pub fn total_value_after_fees(&self) -> Result<BigDecimal> {
+        parse_decimal_field("total_value_after_fees", &self.total_value_after_fees)
+    }
+
+    /// [`Order::outstanding_hold_amount`] as a `BigDecimal`.
+    pub fn outstanding_hold_amount(&self) -> Result<BigDecimal> {
+        parse_decimal_field("outstanding_hold_amount", &self.outstanding_hold_amount)
+    }
+}
+
+fn parse_decimal_field(name: &str, value: &str) -> Result<BigDecimal> {
+    anyhow::ensure!(!value.is_empty(), "Field {} is empty", name);
+    BigDecimal::from_str(value).map_err(|err| anyhow!("Invalid {} {:?}: {}", name, value, err))
+}
+
 #[doc(hidden)]
 /// Structure representing Coinbase's wrapped response for a single order
 #[derive(Deserialize, Debug)]
@@ -207,18 +452,192 @@ pub struct OrderResponse {
 pub type OrderSide = crate::products::Side;
 pub type TradeType = crate::products::TradeType;
 
-#[doc(hidden)]
+/// Query parameters to list orders, see
+/// [list_orders_with_query()](`crate::client::CbClient::list_orders_with_query`).
+///
+/// All parameters are optional:
+/// ```
+/// # use coinbase_v3::orders::{OrderSide, OrdersQuery, Status};
+/// let query = OrdersQuery::new()
+///     .product_id("BTC-USD")
+///     .order_status(vec![Status::Open])
+///     .order_side(OrderSide::Buy)
+///     .limit(50);
+/// ```
+#[derive(Debug, Default)]
+pub struct OrdersQuery {
+    pub(crate) product_id: Option<String>,
+    pub(crate) order_status: Option<Vec<Status>>,
+    pub(crate) limit: Option<i32>,
+    pub(crate) start_date: Option<DateTime>,
+    pub(crate) end_date: Option<DateTime>,
+    pub(crate) deprecated_user_native_currency: Option<String>,
+    pub(crate) order_type: Option<OrderType>,
+    pub(crate) order_side: Option<OrderSide>,
+    pub(crate) cursor: Option<String>,
+    pub(crate) product_type: Option<ProductType>,
+    pub(crate) order_placement_source: Option<OrderPlacementSource>,
+    pub(crate) contract_expiry_type: Option<ContractExpiryType>,
+}
+
+impl OrdersQuery {
+    /// Instantiate a query without any filter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only list orders for this product.
+    pub fn product_id(mut self, product_id: &str) -> Self {
+        self.product_id = Some(product_id.to_string());
+        self
+    }
+
+    /// Only list orders with one of these statuses. Leave unset to list orders of all statuses.
+    pub fn order_status(mut self, order_status: Vec<Status>) -> Self {
+        self.order_status = Some(order_status);
+        self
+    }
+
+    /// Number of orders per page.
+    pub fn limit(mut self, limit: i32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Only list orders created after this date.
+    pub fn start_date(mut self, start_date: DateTime) -> Self {
+        self.start_date = Some(start_date);
+        self
+    }
+
+    /// Only list orders created before this date.
+    pub fn end_date(mut self, end_date: DateTime) -> Self {
+        self.end_date = Some(end_date);
+        self
+    }
+
+    /// Deprecated by Coinbase: native currency of the user.
+    pub fn deprecated_user_native_currency(mut self, currency: &str) -> Self {
+        self.deprecated_user_native_currency = Some(currency.to_string());
+        self
+    }
+
+    /// Only list orders of this type.
+    pub fn order_type(mut self, order_type: OrderType) -> Self {
+        self.order_type = Some(order_type);
+        self
+    }
+
+    /// Only list orders on this side.
+    pub fn order_side(mut self, order_side: OrderSide) -> Self {
+        self.order_side = Some(order_side);
+        self
+    }
+
+    /// Start listing from this pagination cursor.
+    pub fn cursor(mut self, cursor: &str) -> Self {
+        self.cursor = Some(cursor.to_string());
+        self
+    }
+
+    /// Only list orders for products of this type.
+    pub fn product_type(mut self, product_type: ProductType) -> Self {
+        self.product_type = Some(product_type);
+        self
+    }
+
+    /// Only list orders placed from this source.
+    pub fn order_placement_source(mut self, order_placement_source: OrderPlacementSource) -> Self {
+        self.order_placement_source = Some(order_placement_source);
+        self
+    }
+
+    /// Only list orders for futures with this expiry type.
+    pub fn contract_expiry_type(mut self, contract_expiry_type: ContractExpiryType) -> Self {
+        self.contract_expiry_type = Some(contract_expiry_type);
+        self
+    }
+}
+
 /// Structure representing Coinbase's wrapper response for multiple orders
+///
+/// Calls to this [Client][`crate::client::CbClient`]'s API will mostly not return this type. They will
+/// unpack the inner `orders` and return it, except for
+/// [list_orders_page()](`crate::client::CbClient::list_orders_page`).
+///
+/// `has_next` and `cursor` are used for pagination.
 #[derive(Deserialize, Debug)]
 pub struct OrdersResponse {
     pub orders: Vec<Order>,
+    /// Sequence number of the response, to detect gaps between pages.
     pub sequence: String,
     pub has_next: bool,
     pub cursor: String,
 }
 
+/// Query parameters to list fills, see
+/// [list_fills_with_query()](`crate::client::CbClient::list_fills_with_query`).
+///
+/// All parameters are optional:
+/// ```
+/// # use coinbase_v3::orders::FillsQuery;
+/// let query = FillsQuery::new().product_id("BTC-USD").limit(50);
+/// ```
+#[derive(Debug, Default)]
+pub struct FillsQuery {
+    pub(crate) order_id: Option<String>,
+    pub(crate) product_id: Option<String>,
+    pub(crate) start_sequence_timestamp: Option<DateTime>,
+    pub(crate) end_sequence_timestamp: Option<DateTime>,
+    pub(crate) limit: Option<i64>,
+    pub(crate) cursor: Option<String>,
+}
+
+impl FillsQuery {
+    /// Instantiate a query without any filter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only list fills of this order.
+    pub fn order_id(mut self, order_id: &str) -> Self {
+        self.order_id = Some(order_id.to_string());
+        self
+    }
+
+    /// Only list fills for this product.
+    pub fn product_id(mut self, product_id: &str) -> Self {
+        self.product_id = Some(product_id.to_string());
+        self
+    }
+
+    /// Only list fills that happened after this date.
+    pub fn start_sequence_timestamp(mut self, start_sequence_timestamp: DateTime) -> Self {
+        self.start_sequence_timestamp = Some(start_sequence_timestamp);
+        self
+    }
+
+    /// Only list fills that happened before this date.
+    pub fn end_sequence_timestamp(mut self, end_sequence_timestamp: DateTime) -> Self {
+        self.end_sequence_timestamp = Some(end_sequence_timestamp);
+        self
+    }
+
+    /// Number of fills per page.
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Start listing from this pagination cursor.
+    pub fn cursor(mut self, cursor: &str) -> Self {
+        self.cursor = Some(cursor.to_string());
+        self
+    }
+}
+
 /// Structure representing CB's response to a fill request
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Fill {
     /// Unique identifier for the fill.
     pub entry_id: String,
@@ -268,7 +687,11 @@ pub struct FillsResponse {
 }
 
 /// Structure to fill to create a new request to be sent to CB
-#[derive(Serialize, Debug)]
+///
+/// Also deserializable, e.g. to save orders to a file and submit them later. Deserialization
+/// fails unless the order has a non-empty `client_order_id` and exactly one configuration.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[serde(try_from = "UncheckedOrderToSend")]
 pub struct OrderToSend {
     /// Client set unique uuid for this order
     client_order_id: String,
@@ -279,6 +702,90 @@ pub struct OrderToSend {
     order_configuration: OrderConfiguration,
 }
 
+impl OrderToSend {
+    /// Client set unique id of the order, to correlate it with later fills or cancels.
+    pub fn client_order_id(&self) -> &str {
+        &self.client_order_id
+    }
+
+    /// The product this order was created for e.g. 'BTC-USD'
+    pub fn product_id(&self) -> &str {
+        &self.product_id
+    }
+
+    /// Configuration of the order: its type, sizes and prices.
+    pub fn order_configuration(&self) -> &OrderConfiguration {
+        &self.order_configuration
+    }
+
+    /// Replace the randomly generated `client_order_id` by `client_order_id`.
+    ///
+    /// Coinbase deduplicates orders sharing the same `client_order_id`: an order whose response
+    /// was lost, e.g. after a crash, can be safely resubmitted with the same id.
+    ///
+    /// ```
+    /// # use coinbase_v3::orders::{create_market_order, OrderSide};
+    /// let order = create_market_order("BTC-USD", OrderSide::Buy, 10.0)
+    ///     .unwrap()
+    ///     .with_client_order_id("my-order-42")
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Fails if `client_order_id` is empty.
+    pub fn with_client_order_id(mut self, client_order_id: &str) -> Result<Self> {
+        anyhow::ensure!(
+            !client_order_id.trim().is_empty(),
+            "client_order_id should not be empty"
+        );
+        self.client_order_id = client_order_id.to_string();
+        Ok(self)
+    }
+
+    /// View of the order as expected by the preview endpoint, which takes no `client_order_id`.
+    pub(crate) fn as_preview(&self) -> OrderToPreview<'_> {
+        OrderToPreview {
+            product_id: &self.product_id,
+            side: &self.side,
+            order_configuration: &self.order_configuration,
+        }
+    }
+}
+
+/// [`OrderToSend`] as read from a file, before validation.
+#[derive(Deserialize)]
+struct UncheckedOrderToSend {
+    client_order_id: String,
+    product_id: String,
+    side: OrderSide,
+    order_configuration: OrderConfiguration,
+}
+
+impl TryFrom<UncheckedOrderToSend> for OrderToSend {
+    type Error = anyhow::Error;
+
+    fn try_from(order: UncheckedOrderToSend) -> Result<Self> {
+        anyhow::ensure!(
+            !order.client_order_id.trim().is_empty(),
+            "client_order_id should not be empty"
+        );
+        order.order_configuration.validate()?;
+        Ok(OrderToSend {
+            client_order_id: order.client_order_id,
+            product_id: order.product_id,
+            side: order.side,
+            order_configuration: order.order_configuration,
+        })
+    }
+}
+
+#[doc(hidden)]
+#[derive(Serialize, Debug)]
+pub struct OrderToPreview<'a> {
+    product_id: &'a str,
+    side: &'a OrderSide,
+    order_configuration: &'a OrderConfiguration,
+}
+
 /// Enum representing the possible values for failure to create an order
 #[derive(Deserialize_enum_str, Serialize_enum_str, Debug, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -299,6 +806,9 @@ pub enum CreateOrderFailureReason {
     InvalidRequest,
     CommanderRejectedNewOrder,
     InsufficientFunds,
+    /// Any value not known by this crate.
+    #[serde(other)]
+    Unknown(String),
 }
 
 /// Enum representing the possible values for failure to preview create an order
@@ -368,6 +878,27 @@ pub struct CreateOrderResponse {
     pub order_configuration: OrderConfiguration,
 }
 
+/// Structure representing CB's response to a preview order request
+#[derive(Deserialize, Debug)]
+pub struct PreviewOrderResponse {
+    /// Total amount of the order, fees included, in quote currency.
+    pub order_total: BigDecimal,
+    /// Fees of the order, in quote currency.
+    pub commission_total: BigDecimal,
+    /// Reasons why the order would fail.
+    #[serde(rename = "errs")]
+    pub errors: Vec<PreviewCreateOrderFailureReason>,
+    pub warnings: Vec<String>,
+    pub quote_size: BigDecimal,
+    pub base_size: BigDecimal,
+    pub best_bid: BigDecimal,
+    pub best_ask: BigDecimal,
+    /// Whether the order uses the maximum available balance.
+    pub is_max: bool,
+    /// Expected slippage of the order.
+    pub slippage: Option<BigDecimal>,
+}
+
 /// Enum representating the possible values for CB failing to cancel an order
 #[derive(Deserialize_enum_str, Serialize_enum_str, Debug, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -377,6 +908,9 @@ pub enum CancelOrderFailureReason {
     UnknownCancelOrder,
     CommanderRejectedCancelOrder,
     DuplicateCancelRequest,
+    /// Any value not known by this crate.
+    #[serde(other)]
+    Unknown(String),
 }
 
 /// Structure representing CB's response to a cancel order request
@@ -395,6 +929,53 @@ pub struct CancelOrdersResponse {
     pub results: Vec<CancelOrderResponse>,
 }
 
+/// Structure to fill to edit an existing order, to be sent to CB
+#[derive(Serialize, Debug)]
+pub struct OrderEdit {
+    /// ID of the order to edit
+    order_id: String,
+    /// New limit price for the order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    price: Option<BigDecimal>,
+    /// New size for the order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<BigDecimal>,
+}
+
+/// Enum representing the possible values for CB failing to edit an order
+#[derive(Deserialize_enum_str, Serialize_enum_str, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EditOrderFailureReason {
+    UnknownEditOrderFailureReason,
+    CommanderRejectedEditOrder,
+    CannotEditToBelowFilledSize,
+    OrderNotFound,
+    CallerIdMismatch,
+    OnlyLimitOrderEditsSupported,
+    InvalidEditedSize,
+    InvalidEditedPrice,
+    InvalidOriginalSize,
+    InvalidOriginalPrice,
+    EditRequestEqualToOriginalRequest,
+    OnlyOpenOrdersCanBeEdited,
+}
+
+/// Structure representing an error returned by CB when editing an order
+#[derive(Deserialize, Debug)]
+pub struct EditOrderError {
+    pub edit_failure_reason: Option<EditOrderFailureReason>,
+    pub preview_failure_reason: Option<PreviewCreateOrderFailureReason>,
+}
+
+/// Structure representing CB's response to an edit order request
+#[derive(Deserialize, Debug)]
+pub struct EditOrderResponse {
+    /// Whether the order was edited.
+    pub success: bool,
+    pub errors: Vec<EditOrderError>,
+    pub warnings: Option<Vec<String>>,
+}
+
 /// Create a MARKET order
 ///
 /// `side` (Buy or Sell) `product_id` for an amount of `order_size`
@@ -429,16 +1010,73 @@ pub fn create_market_order(
         client_order_id,
         product_id: product_id.to_string(),
         side,
-        order_configuration: OrderConfiguration {
-            market_market_ioc: Some(Market {
+        order_configuration: OrderConfiguration::builder()
+            .market_market_ioc(Market {
                 base_size,
                 quote_size,
-            }),
-            limit_limit_gtc: None,
-            limit_limit_gtd: None,
-            stop_limit_stop_limit_gtc: None,
-            stop_limit_stop_limit_gtd: None,
-        },
+            })
+            .build()?,
+    };
+    Ok(order)
+}
+
+/// Create a MARKET order sized in base currency
+///
+/// `side` (Buy or Sell) `product_id` for an amount of `base_size`, whatever the side.
+///
+/// returns an [`OrderToSend`] struct filled with relevant values. Does not make the actual order.
+pub fn create_market_order_base_size(
+    product_id: &str,
+    side: OrderSide,
+    base_size: f64,
+) -> Result<OrderToSend> {
+    anyhow::ensure!(
+        side == OrderSide::Buy || side == OrderSide::Sell,
+        "Orders' side should be Buy or Sell . Got: {:?}",
+        side
+    );
+
+    let order = OrderToSend {
+        client_order_id: uuid::Uuid::new_v4().to_string(),
+        product_id: product_id.to_string(),
+        side,
+        order_configuration: OrderConfiguration::builder()
+            .market_market_ioc(Market {
+                base_size: Some(f64_to_valid_bigdecimal(base_size)?),
+                quote_size: None,
+            })
+            .build()?,
+    };
+    Ok(order)
+}
+
+/// Create a smart-order-routing LIMIT Immediate-Or-Cancel order
+///
+/// `side` (Buy or Sell) `product_id` for an amount of `base_size` at a price of `limit_price`
+///
+/// returns an [`OrderToSend`] struct filled with relevant values. Does not make the actual order.
+pub fn create_sor_limit_ioc_order(
+    product_id: &str,
+    side: OrderSide,
+    base_size: f64,
+    limit_price: f64,
+) -> Result<OrderToSend> {
+    anyhow::ensure!(
+        side == OrderSide::Buy || side == OrderSide::Sell,
+        "Orders' side should be Buy or Sell . Got: {:?}",
+        side
+    );
+
+    let order = OrderToSend {
+        client_order_id: uuid::Uuid::new_v4().to_string(),
+        product_id: product_id.to_string(),
+        side,
+        order_configuration: OrderConfiguration::builder()
+            .sor_limit_ioc(SorLimitIoc {
+                base_size: f64_to_valid_bigdecimal(base_size)?,
+                limit_price: f64_to_valid_bigdecimal(limit_price)?,
+            })
+            .build()?,
     };
     Ok(order)
 }
@@ -468,18 +1106,14 @@ pub fn create_limit_order_good_til_canceled(
         client_order_id,
         product_id: product_id.to_string(),
         side,
-        order_configuration: OrderConfiguration {
-            market_market_ioc: None,
-            limit_limit_gtc: Some(Limit {
+        order_configuration: OrderConfiguration::builder()
+            .limit_limit_gtc(Limit {
                 base_size,
                 limit_price,
                 end_time: None,
                 post_only: Some(post_only),
-            }),
-            limit_limit_gtd: None,
-            stop_limit_stop_limit_gtc: None,
-            stop_limit_stop_limit_gtd: None,
-        },
+            })
+            .build()?,
     };
     Ok(order)
 }
@@ -510,18 +1144,14 @@ pub fn create_limit_order_good_til_date(
         client_order_id,
         product_id: product_id.to_string(),
         side,
-        order_configuration: OrderConfiguration {
-            market_market_ioc: None,
-            limit_limit_gtc: None,
-            limit_limit_gtd: Some(Limit {
+        order_configuration: OrderConfiguration::builder()
+            .limit_limit_gtd(Limit {
                 base_size: size,
                 limit_price: price,
                 end_time: Some(end_time),
                 post_only: Some(post_only),
-            }),
-            stop_limit_stop_limit_gtc: None,
-            stop_limit_stop_limit_gtd: None,
-        },
+            })
+            .build()?,
     };
     Ok(order)
 }
@@ -553,19 +1183,15 @@ pub fn create_stop_limit_order_good_til_canceled(
         client_order_id,
         product_id: product_id.to_string(),
         side,
-        order_configuration: OrderConfiguration {
-            market_market_ioc: None,
-            limit_limit_gtc: None,
-            limit_limit_gtd: None,
-            stop_limit_stop_limit_gtc: Some(StopLimit {
+        order_configuration: OrderConfiguration::builder()
+            .stop_limit_stop_limit_gtc(StopLimit {
                 base_size,
                 limit_price,
                 stop_price,
                 end_time: None,
                 stop_direction,
-            }),
-            stop_limit_stop_limit_gtd: None,
-        },
+            })
+            .build()?,
     };
     Ok(order)
 }
@@ -598,27 +1224,254 @@ pub fn create_stop_limit_order_good_til_date(
         client_order_id,
         product_id: product_id.to_string(),
         side,
-        order_configuration: OrderConfiguration {
-            market_market_ioc: None,
-            limit_limit_gtc: None,
-            limit_limit_gtd: None,
-            stop_limit_stop_limit_gtc: None,
-            stop_limit_stop_limit_gtd: Some(StopLimit {
+        order_configuration: OrderConfiguration::builder()
+            .stop_limit_stop_limit_gtd(StopLimit {
                 base_size,
                 limit_price,
                 stop_price,
                 end_time: Some(end_time),
                 stop_direction,
-            }),
-        },
+            })
+            .build()?,
+    };
+    Ok(order)
+}
+
+/// Create a TRIGGER-BRACKET Good-Til-Canceled order
+///
+/// `side` (Buy or Sell) `product_id` for an amount of `base_size` at a price of `limit_price`,
+/// with a stop-loss at `stop_trigger_price`
+///
+/// returns an [`OrderToSend`] struct filled with relevant values. Does not make the actual order.
+pub fn create_trigger_bracket_order_good_til_canceled(
+    product_id: &str,
+    side: OrderSide,
+    base_size: f64,
+    limit_price: f64,
+    stop_trigger_price: f64,
+) -> Result<OrderToSend> {
+    let client_order_id = uuid::Uuid::new_v4().to_string();
+    anyhow::ensure!(
+        side == OrderSide::Buy || side == OrderSide::Sell,
+        "Orders' side should be Buy or Sell . Got: {:?}",
+        side
+    );
+    let base_size = f64_to_valid_bigdecimal(base_size)?;
+    let limit_price = f64_to_valid_bigdecimal(limit_price)?;
+    let stop_trigger_price = f64_to_valid_bigdecimal(stop_trigger_price)?;
+
+    let order = OrderToSend {
+        client_order_id,
+        product_id: product_id.to_string(),
+        side,
+        order_configuration: OrderConfiguration::builder()
+            .trigger_bracket_gtc(TriggerBracket {
+                base_size,
+                limit_price,
+                stop_trigger_price,
+                end_time: None,
+            })
+            .build()?,
+    };
+    Ok(order)
+}
+
+/// Create a TRIGGER-BRACKET Good-Til-Date order
+///
+/// `side` (Buy or Sell) `product_id` for an amount of `base_size` at a price of `limit_price`,
+/// with a stop-loss at `stop_trigger_price`
+///
+/// returns an [`OrderToSend`] struct filled with relevant values. Does not make the actual order.
+pub fn create_trigger_bracket_order_good_til_date(
+    product_id: &str,
+    side: OrderSide,
+    base_size: f64,
+    limit_price: f64,
+    stop_trigger_price: f64,
+    end_time: DateTime,
+) -> Result<OrderToSend> {
+    let client_order_id = uuid::Uuid::new_v4().to_string();
+    anyhow::ensure!(
+        side == OrderSide::Buy || side == OrderSide::Sell,
+        "Orders' side should be Buy or Sell . Got: {:?}",
+        side
+    );
+    let base_size = f64_to_valid_bigdecimal(base_size)?;
+    let limit_price = f64_to_valid_bigdecimal(limit_price)?;
+    let stop_trigger_price = f64_to_valid_bigdecimal(stop_trigger_price)?;
+
+    let order = OrderToSend {
+        client_order_id,
+        product_id: product_id.to_string(),
+        side,
+        order_configuration: OrderConfiguration::builder()
+            .trigger_bracket_gtd(TriggerBracket {
+                base_size,
+                limit_price,
+                stop_trigger_price,
+                end_time: Some(end_time),
+            })
+            .build()?,
     };
     Ok(order)
 }
 
+/// Create an edit of an existing order
+///
+/// Only open limit orders can be edited, by changing their `price` and/or their `size`.
+///
+/// returns an [`OrderEdit`] struct filled with relevant values. Does not make the actual edit.
+pub fn create_order_edit(
+    order_id: &str,
+    price: Option<f64>,
+    size: Option<f64>,
+) -> Result<OrderEdit> {
+    anyhow::ensure!(
+        price.is_some() || size.is_some(),
+        "Editing an order requires a new price or a new size"
+    );
+    let price = price.map(f64_to_valid_bigdecimal).transpose()?;
+    let size = size.map(f64_to_valid_bigdecimal).transpose()?;
+
+    Ok(OrderEdit {
+        order_id: order_id.to_string(),
+        price,
+        size,
+    })
+}
+
+/// Check that the final state of an order is consistent with its time in force.
+///
+/// Coinbase settles orders asynchronously, so the response to a create order request does not
+/// tell how much of the order got filled. Once the order is terminal:
+/// - a `FILL_OR_KILL` order should be fully filled,
+/// - an `IMMEDIATE_OR_CANCEL` order should be filled, or cancelled with a possible partial fill.
+///
+/// Returns an error if the order is not terminal yet, if it has another time in force, or if its
+/// state contradicts its time in force.
+pub fn verify_time_in_force(order: &Order) -> Result<()> {
+    let filled_size = if order.filled_size.is_empty() {
+        BigDecimal::from(0)
+    } else {
+        order.filled_size()?
+    };
+    let partially_filled = filled_size > BigDecimal::from(0);
+
+    match (&order.time_in_force, &order.status) {
+        (TimeInForce::FillOrKill | TimeInForce::ImmediateOrCancel, status)
+            if !status.is_terminal() =>
+        {
+            Err(anyhow!(
+                "Order {} is not terminal yet. Got: {:?}",
+                order.order_id,
+                order.status
+            ))
+        }
+        (TimeInForce::FillOrKill, Status::Filled) => Ok(()),
+        (TimeInForce::FillOrKill, _) if partially_filled => Err(anyhow!(
+            "Fill-or-kill order {} is {:?} with a partial fill of {}",
+            order.order_id,
+            order.status,
+            order.filled_size
+        )),
+        (TimeInForce::FillOrKill, _) => Err(anyhow!(
+            "Fill-or-kill order {} was not filled. Got: {:?}",
+            order.order_id,
+            order.status
+        )),
+        (TimeInForce::ImmediateOrCancel, Status::Filled | Status::Cancelled | Status::Expired) => {
+            Ok(())
+        }
+        (TimeInForce::ImmediateOrCancel, _) => Err(anyhow!(
+            "Immediate-or-cancel order {} was not filled. Got: {:?}",
+            order.order_id,
+            order.status
+        )),
+        _ => Err(anyhow!(
+            "Order {} is neither fill-or-kill nor immediate-or-cancel. Got: {:?}",
+            order.order_id,
+            order.time_in_force
+        )),
+    }
+}
+
+/// Compute the slippage of a filled order, in basis points, relative to `reference_price`.
+///
+/// `reference_price` is typically the mid-market price when the order was submitted. The
+/// slippage is `(average_filled_price - reference_price) / reference_price`, signed such that a
+/// positive value is always unfavorable: buying above or selling below the reference price.
+///
+/// Returns an error if the order is not filled.
+pub fn slippage_bps(order: &Order, reference_price: &BigDecimal) -> Result<BigDecimal> {
+    anyhow::ensure!(
+        order.status == Status::Filled,
+        "Order {} is not filled. Got: {:?}",
+        order.order_id,
+        order.status
+    );
+    anyhow::ensure!(
+        *reference_price != BigDecimal::from(0),
+        "Reference price should not be zero"
+    );
+    let average_filled_price = order.average_filled_price()?;
+
+    let slippage =
+        (average_filled_price - reference_price) / reference_price * BigDecimal::from(10_000);
+    match order.side {
+        OrderSide::Buy => Ok(slippage),
+        OrderSide::Sell => Ok(-slippage),
+        _ => Err(anyhow!(
+            "Orders' side should be Buy or Sell . Got: {:?}",
+            order.side
+        )),
+    }
+}
+
+/// Maximum number of orders Coinbase accepts in a single batch cancel request.
+pub const MAX_CANCEL_BATCH_SIZE: usize = 100;
+
+/// Split the IDs of the open orders, for `product_id` if given, into batches small enough for a
+/// single batch cancel request.
+///
+/// Orders for other products or not open anymore are skipped.
+pub fn cancel_batches(orders: &[Order], product_id: Option<&str>) -> Vec<Vec<String>> {
+    let order_ids: Vec<String> = orders
+        .iter()
+        .filter(|order| product_id.map_or(true, |product_id| order.product_id == product_id))
+        .filter(|order| order.status == Status::Open)
+        .map(|order| order.order_id.clone())
+        .collect();
+
+    order_ids
+        .chunks(MAX_CANCEL_BATCH_SIZE)
+        .map(|chunk| chunk.to_vec())
+        .collect()
+}
+
+/// Split the results of a batch cancel request into the IDs of the cancelled orders and the IDs
+/// of the orders that could not be cancelled, along with the reason.
+pub fn split_cancel_results(
+    results: Vec<CancelOrderResponse>,
+) -> (Vec<String>, Vec<(String, CancelOrderFailureReason)>) {
+    let mut cancelled = Vec::new();
+    let mut failed = Vec::new();
+    for result in results {
+        if result.success {
+            cancelled.push(result.order_id);
+        } else {
+            let reason = result
+                .failure_reason
+                .unwrap_or(CancelOrderFailureReason::UnknownCancelFailureReason);
+            failed.push((result.order_id, reason));
+        }
+    }
+    (cancelled, failed)
+}
+
 /// Converting a f64 to a Result<BigDecimal> instead of an Option<BigDecimal>
 ///
 /// Useful for instance when creating an order and failure is preferred to a non-relevant value.
-fn f64_to_valid_bigdecimal(x: f64) -> Result<BigDecimal> {
+pub(crate) fn f64_to_valid_bigdecimal(x: f64) -> Result<BigDecimal> {
     FromPrimitive::from_f64(x).ok_or(anyhow!("Could not convert {} to BigDecimal", x))
 }
 
@@ -627,6 +1480,43 @@ fn f64_to_valid_bigdecimal(x: f64) -> Result<BigDecimal> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::assert_enum_round_trip;
+
+    #[test]
+    fn test_enums_round_trip() {
+        assert_enum_round_trip(&[
+            (Status::Open, "OPEN"),
+            (Status::Filled, "FILLED"),
+            (Status::Cancelled, "CANCELLED"),
+            (Status::Expired, "EXPIRED"),
+            (Status::Failed, "FAILED"),
+            (Status::UnknownOrderStatus, "UNKNOWN_ORDER_STATUS"),
+        ]);
+        assert_enum_round_trip(&[
+            (OrderType::UnknownOrderType, "UNKNOWN_ORDER_TYPE"),
+            (OrderType::Market, "MARKET"),
+            (OrderType::Limit, "LIMIT"),
+            (OrderType::Stop, "STOP"),
+            (OrderType::StopLimitOrderType, "STOP_LIMIT"),
+        ]);
+        assert_enum_round_trip(&[
+            (TimeInForce::UnknownTimeInForce, "UNKNOWN_TIME_IN_FORCE"),
+            (TimeInForce::GoodUntilDateTime, "GOOD_UNTIL_DATE_TIME"),
+            (TimeInForce::GoodUntilCancelled, "GOOD_UNTIL_CANCELLED"),
+            (TimeInForce::ImmediateOrCancel, "IMMEDIATE_OR_CANCEL"),
+            (TimeInForce::FillOrKill, "FILL_OR_KILL"),
+        ]);
+        assert_enum_round_trip(&[
+            (
+                TriggerStatus::UnknownTriggerStatus,
+                "UNKNOWN_TRIGGER_STATUS",
+            ),
+            (TriggerStatus::InvalidOrderType, "INVALID_ORDER_TYPE"),
+            (TriggerStatus::StopPending, "STOP_PENDING"),
+            (TriggerStatus::StopTriggered, "STOP_TRIGGERED"),
+        ]);
+    }
+
     #[test]
     fn test_order_deserialize() {
         let input = r##"{
@@ -703,6 +1593,149 @@ mod tests {
             .unwrap());
     }
 
+    #[test]
+    fn test_filled_order_without_optional_fields_deserialize() {
+        let input = r##"{
+            "order_id": "0000-000000-000000",
+            "product_id": "BTC-USD",
+            "user_id": "2222-000000-000000",
+            "order_configuration": {
+                "market_market_ioc": {
+                    "quote_size": "10.00"
+                }
+            },
+            "side": "BUY",
+            "client_order_id": "11111-000000-000000",
+            "status": "FILLED",
+            "time_in_force": "IMMEDIATE_OR_CANCEL",
+            "created_time": "2021-05-31T09:59:59Z",
+            "completion_percentage": "100",
+            "filled_size": "0.0004",
+            "average_filled_price": "25000",
+            "fee": "",
+            "number_of_fills": "1",
+            "filled_value": "10",
+            "pending_cancel": false,
+            "size_in_quote": true,
+            "total_fees": "0.06",
+            "size_inclusive_of_fees": true,
+            "total_value_after_fees": "10.06",
+            "order_type": "MARKET",
+            "settled": true,
+            "product_type": "SPOT",
+            "order_placement_source": "RETAIL_ADVANCED",
+            "outstanding_hold_amount": "0",
+            "is_liquidation": false
+        }"##;
+        let order: Order = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(order.status, Status::Filled);
+        assert_eq!(order.reject_reason, None);
+        assert_eq!(order.trigger_status, None);
+        assert_eq!(order.reject_message, None);
+        assert_eq!(order.cancel_message, None);
+    }
+
+    #[test]
+    fn test_order_to_send_serde_round_trip() {
+        let orders = vec![
+            create_market_order("BTC-USD", OrderSide::Buy, 10.0).unwrap(),
+            create_limit_order_good_til_canceled("ETH-USD", OrderSide::Sell, 0.5, 2000.0, true)
+                .unwrap(),
+        ];
+        let json = serde_json::to_string(&orders).unwrap();
+        let result: Vec<OrderToSend> = serde_json::from_str(&json).unwrap();
+        assert_eq!(result, orders);
+        assert_eq!(result[1].product_id(), "ETH-USD");
+        assert_eq!(result[1].client_order_id(), orders[1].client_order_id());
+    }
+
+    #[test]
+    fn test_order_to_send_deserialize_rejects_invalid_orders() {
+        let no_configuration = r##"{
+            "client_order_id": "my-order-42",
+            "product_id": "BTC-USD",
+            "side": "BUY",
+            "order_configuration": {}
+        }"##;
+        assert!(serde_json::from_str::<OrderToSend>(no_configuration).is_err());
+
+        let two_configurations = r##"{
+            "client_order_id": "my-order-42",
+            "product_id": "BTC-USD",
+            "side": "BUY",
+            "order_configuration": {
+                "market_market_ioc": {"quote_size": "10"},
+                "limit_limit_gtc": {"base_size": "0.001", "limit_price": "10000", "post_only": true}
+            }
+        }"##;
+        assert!(serde_json::from_str::<OrderToSend>(two_configurations).is_err());
+
+        let empty_client_order_id = r##"{
+            "client_order_id": " ",
+            "product_id": "BTC-USD",
+            "side": "BUY",
+            "order_configuration": {"market_market_ioc": {"quote_size": "10"}}
+        }"##;
+        let error = serde_json::from_str::<OrderToSend>(empty_client_order_id).unwrap_err();
+        assert!(error.to_string().contains("client_order_id"));
+
+        let valid = r##"{
+            "client_order_id": "my-order-42",
+            "product_id": "BTC-USD",
+            "side": "BUY",
+            "order_configuration": {"market_market_ioc": {"quote_size": "10"}}
+        }"##;
+        let order: OrderToSend = serde_json::from_str(valid).unwrap();
+        assert_eq!(order.client_order_id(), "my-order-42");
+    }
+
+    #[test]
+    fn test_split_cancel_results() {
+        let input = r##"{
+            "results": [
+                {
+                    "success": true,
+                    "failure_reason": "UNKNOWN_CANCEL_FAILURE_REASON",
+                    "order_id": "0000-000001"
+                },
+                {
+                    "success": false,
+                    "failure_reason": "UNKNOWN_CANCEL_ORDER",
+                    "order_id": "not-an-order"
+                },
+                {
+                    "success": false,
+                    "failure_reason": "SOME_NEW_REASON",
+                    "order_id": "0000-000003"
+                },
+                {
+                    "success": false,
+                    "order_id": "0000-000004"
+                }
+            ]
+        }"##;
+        let response: CancelOrdersResponse = serde_json::from_slice(input.as_bytes()).unwrap();
+        let (cancelled, failed) = split_cancel_results(response.results);
+        assert_eq!(cancelled, vec!["0000-000001".to_string()]);
+        assert_eq!(
+            failed,
+            vec![
+                (
+                    "not-an-order".to_string(),
+                    CancelOrderFailureReason::UnknownCancelOrder
+                ),
+                (
+                    "0000-000003".to_string(),
+                    CancelOrderFailureReason::Unknown("SOME_NEW_REASON".to_string())
+                ),
+                (
+                    "0000-000004".to_string(),
+                    CancelOrderFailureReason::UnknownCancelFailureReason
+                ),
+            ]
+        );
+    }
+
     #[test]
     fn test_stop_direction_deserialize() {
         let input = r##""UNKNOWN_STOP_DIRECTION""##;
@@ -843,6 +1876,40 @@ mod tests {
         assert_eq!(result, OrderType::StopLimitOrderType);
     }
 
+    #[test]
+    fn test_status_is_terminal() {
+        assert!(Status::Filled.is_terminal());
+        assert!(Status::Cancelled.is_terminal());
+        assert!(Status::Expired.is_terminal());
+        assert!(Status::Failed.is_terminal());
+        assert!(!Status::Open.is_terminal());
+        assert!(!Status::UnknownOrderStatus.is_terminal());
+        assert!(!Status::Unknown("QUEUED".to_string()).is_terminal());
+    }
+
+    #[test]
+    fn test_order_enums_unknown_value() {
+        let input = r##""NEW_COINBASE_VALUE""##;
+        let result: Status = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(result, Status::Unknown("NEW_COINBASE_VALUE".to_string()));
+        let result: OrderType = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(result, OrderType::Unknown("NEW_COINBASE_VALUE".to_string()));
+        let result: TriggerStatus = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(
+            result,
+            TriggerStatus::Unknown("NEW_COINBASE_VALUE".to_string())
+        );
+        let result: CreateOrderFailureReason = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(
+            result,
+            CreateOrderFailureReason::Unknown("NEW_COINBASE_VALUE".to_string())
+        );
+        assert_eq!(
+            input,
+            serde_json::to_string(&Status::Unknown("NEW_COINBASE_VALUE".to_string())).unwrap()
+        );
+    }
+
     #[test]
     fn test_order_type_serialize() {
         let expected = r##""MARKET""##;
@@ -885,6 +1952,72 @@ mod tests {
         );
     }
 
+    fn sample_limit() -> Limit {
+        Limit {
+            base_size: BigDecimal::from(1),
+            limit_price: BigDecimal::from(10000),
+            end_time: None,
+            post_only: Some(false),
+        }
+    }
+
+    fn sample_stop_limit() -> StopLimit {
+        StopLimit {
+            base_size: BigDecimal::from(1),
+            limit_price: BigDecimal::from(10000),
+            stop_price: BigDecimal::from(20000),
+            stop_direction: StopDirection::StopDirectionStopUp,
+            end_time: None,
+        }
+    }
+
+    #[test]
+    fn test_order_configuration_builder_single_config() {
+        let config = OrderConfiguration::builder()
+            .market_market_ioc(Market {
+                quote_size: Some(BigDecimal::from(10)),
+                base_size: None,
+            })
+            .build()
+            .unwrap();
+        assert!(config.market_market_ioc.is_some());
+
+        let config = OrderConfiguration::builder()
+            .limit_limit_gtc(sample_limit())
+            .build()
+            .unwrap();
+        assert_eq!(config.limit_limit_gtc, Some(sample_limit()));
+
+        let config = OrderConfiguration::builder()
+            .limit_limit_gtd(sample_limit())
+            .build()
+            .unwrap();
+        assert_eq!(config.limit_limit_gtd, Some(sample_limit()));
+
+        let config = OrderConfiguration::builder()
+            .stop_limit_stop_limit_gtc(sample_stop_limit())
+            .build()
+            .unwrap();
+        assert_eq!(config.stop_limit_stop_limit_gtc, Some(sample_stop_limit()));
+
+        let config = OrderConfiguration::builder()
+            .stop_limit_stop_limit_gtd(sample_stop_limit())
+            .build()
+            .unwrap();
+        assert_eq!(config.stop_limit_stop_limit_gtd, Some(sample_stop_limit()));
+    }
+
+    #[test]
+    fn test_order_configuration_builder_invalid() {
+        assert!(OrderConfiguration::builder().build().is_err());
+
+        let result = OrderConfiguration::builder()
+            .limit_limit_gtc(sample_limit())
+            .stop_limit_stop_limit_gtc(sample_stop_limit())
+            .build();
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_create_market_order_serialize() {
         let product_id = "BTC-USD";
@@ -895,6 +2028,103 @@ mod tests {
         assert!(json.is_ok());
     }
 
+    #[test]
+    fn test_create_market_order_base_size_serialize() {
+        let order = create_market_order_base_size("BTC-USD", OrderSide::Buy, 0.5).unwrap();
+        let json = serde_json::to_value(&order).unwrap();
+        assert_eq!(json["side"], "BUY");
+        let market = &json["order_configuration"]["market_market_ioc"];
+        assert!(market["quote_size"].is_null());
+        let base_size = BigDecimal::from_str(market["base_size"].as_str().unwrap()).unwrap();
+        assert_eq!(base_size, BigDecimal::from_str("0.5").unwrap());
+
+        let result = create_market_order_base_size("BTC-USD", OrderSide::UnknownOrderSide, 0.5);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_create_sor_limit_ioc_order_serialize() {
+        let order = create_sor_limit_ioc_order("BTC-USD", OrderSide::Sell, 0.25, 30000.0).unwrap();
+        let json = serde_json::to_value(&order).unwrap();
+        assert_eq!(json["side"], "SELL");
+        let sor_limit_ioc = &json["order_configuration"]["sor_limit_ioc"];
+        let base_size = BigDecimal::from_str(sor_limit_ioc["base_size"].as_str().unwrap()).unwrap();
+        assert_eq!(base_size, BigDecimal::from_str("0.25").unwrap());
+        let limit_price =
+            BigDecimal::from_str(sor_limit_ioc["limit_price"].as_str().unwrap()).unwrap();
+        assert_eq!(limit_price, BigDecimal::from(30000));
+        assert!(json["order_configuration"]["market_market_ioc"].is_null());
+
+        let result = create_sor_limit_ioc_order("BTC-USD", OrderSide::UnknownOrderSide, 0.25, 1.0);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_sor_limit_ioc_deserialize() {
+        let input = r##"{
+            "sor_limit_ioc": {
+                "base_size": "0.001",
+                "limit_price": "10000.00"
+            }
+        }"##;
+        let config: OrderConfiguration = serde_json::from_str(input).unwrap();
+        assert_eq!(
+            config.sor_limit_ioc,
+            Some(SorLimitIoc {
+                base_size: BigDecimal::from_str("0.001").unwrap(),
+                limit_price: BigDecimal::from_str("10000.00").unwrap(),
+            })
+        );
+        assert!(config.limit_limit_gtc.is_none());
+    }
+
+    #[test]
+    fn test_order_to_send_client_order_id() {
+        let order = create_market_order("BTC-USD", OrderSide::Buy, 10.0).unwrap();
+        assert_eq!(order.product_id(), "BTC-USD");
+        assert!(uuid::Uuid::parse_str(order.client_order_id()).is_ok());
+
+        let order = order.with_client_order_id("my-order-42").unwrap();
+        assert_eq!(order.client_order_id(), "my-order-42");
+        let json = serde_json::to_value(&order).unwrap();
+        assert_eq!(json["client_order_id"], "my-order-42");
+    }
+
+    #[test]
+    fn test_supplied_client_order_id_serialize() {
+        let order =
+            create_limit_order_good_til_canceled("BTC-USD", OrderSide::Sell, 0.1, 50000.0, true)
+                .unwrap()
+                .with_client_order_id("0a1b2c3d-retry-safe")
+                .unwrap();
+        let json = serde_json::to_string(&order).unwrap();
+        assert!(json.contains(r#""client_order_id":"0a1b2c3d-retry-safe""#));
+
+        let order = create_market_order("BTC-USD", OrderSide::Buy, 10.0).unwrap();
+        assert!(order.with_client_order_id("").is_err());
+        let order = create_market_order("BTC-USD", OrderSide::Buy, 10.0).unwrap();
+        assert!(order.with_client_order_id("  ").is_err());
+    }
+
+    #[test]
+    fn test_market_order_configuration_single_size() {
+        let result = OrderConfiguration::builder()
+            .market_market_ioc(Market {
+                quote_size: Some(BigDecimal::from(10)),
+                base_size: Some(BigDecimal::from(1)),
+            })
+            .build();
+        assert!(result.is_err());
+
+        let result = OrderConfiguration::builder()
+            .market_market_ioc(Market {
+                quote_size: None,
+                base_size: None,
+            })
+            .build();
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_create_limit_order_good_til_canceled_serialize() {
         let product_id = "BTC-USD";
@@ -979,6 +2209,47 @@ mod tests {
         assert!(json.is_ok());
     }
 
+    #[test]
+    fn test_create_trigger_bracket_order_good_til_canceled_serde() {
+        let order = create_trigger_bracket_order_good_til_canceled(
+            "BTC-USD",
+            OrderSide::Sell,
+            0.5,
+            30000.0,
+            25000.0,
+        )
+        .unwrap();
+        let json = serde_json::to_string(&order.order_configuration).unwrap();
+        let round_trip: OrderConfiguration = serde_json::from_str(&json).unwrap();
+        assert!(round_trip.market_market_ioc.is_none());
+        let config = round_trip.trigger_bracket_gtc.unwrap();
+        assert_eq!(config.base_size, BigDecimal::from_str("0.5").unwrap());
+        assert_eq!(config.limit_price, BigDecimal::from(30000));
+        assert_eq!(config.stop_trigger_price, BigDecimal::from(25000));
+        assert!(config.end_time.is_none());
+    }
+
+    #[test]
+    fn test_create_trigger_bracket_order_good_til_date_serde() {
+        let end_time = chrono::offset::Utc::now(); // good enough for serde test
+        let order = create_trigger_bracket_order_good_til_date(
+            "BTC-USD",
+            OrderSide::Buy,
+            0.5,
+            30000.0,
+            25000.0,
+            end_time,
+        )
+        .unwrap();
+        let json = serde_json::to_string(&order.order_configuration).unwrap();
+        let round_trip: OrderConfiguration = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_trip, order.order_configuration);
+        assert_eq!(
+            round_trip.trigger_bracket_gtd.unwrap().end_time,
+            Some(end_time)
+        );
+    }
+
     #[test]
     fn test_order_response_serde() {
         let input = r##"{
@@ -1070,6 +2341,174 @@ mod tests {
             .unwrap());
     }
 
+    fn order_with_state(time_in_force: TimeInForce, status: Status, filled_size: &str) -> Order {
+        let input = r##"{
+            "order_id": "0000-000000-000000",
+            "product_id": "BTC-USD",
+            "user_id": "2222-000000-000000",
+            "order_configuration": {},
+            "side": "BUY",
+            "client_order_id": "11111-000000-000000",
+            "status": "OPEN",
+            "time_in_force": "UNKNOWN_TIME_IN_FORCE",
+            "created_time": "2021-05-31T09:59:59Z",
+            "completion_percentage": "0",
+            "filled_size": "0",
+            "average_filled_price": "0",
+            "fee": "",
+            "number_of_fills": "0",
+            "filled_value": "0",
+            "pending_cancel": false,
+            "size_in_quote": false,
+            "total_fees": "0",
+            "size_inclusive_of_fees": false,
+            "total_value_after_fees": "0",
+            "trigger_status": "INVALID_ORDER_TYPE",
+            "order_type": "LIMIT",
+            "reject_reason": "REJECT_REASON_UNSPECIFIED",
+            "settled": false,
+            "product_type": "SPOT",
+            "order_placement_source": "RETAIL_ADVANCED",
+            "outstanding_hold_amount": "0",
+            "is_liquidation": false
+        }"##;
+        let mut order: Order = serde_json::from_slice(input.as_bytes()).unwrap();
+        order.time_in_force = time_in_force;
+        order.status = status;
+        order.filled_size = filled_size.to_string();
+        order
+    }
+
+    #[test]
+    fn test_verify_time_in_force_fill_or_kill() {
+        let order = order_with_state(TimeInForce::FillOrKill, Status::Filled, "1.5");
+        assert!(verify_time_in_force(&order).is_ok());
+
+        let order = order_with_state(TimeInForce::FillOrKill, Status::Cancelled, "0");
+        assert!(verify_time_in_force(&order).is_err());
+
+        let order = order_with_state(TimeInForce::FillOrKill, Status::Cancelled, "0.5");
+        assert!(verify_time_in_force(&order).is_err());
+
+        let order = order_with_state(TimeInForce::FillOrKill, Status::Open, "");
+        assert!(verify_time_in_force(&order).is_err());
+    }
+
+    #[test]
+    fn test_verify_time_in_force_immediate_or_cancel() {
+        let order = order_with_state(TimeInForce::ImmediateOrCancel, Status::Filled, "1.5");
+        assert!(verify_time_in_force(&order).is_ok());
+
+        let order = order_with_state(TimeInForce::ImmediateOrCancel, Status::Cancelled, "0.5");
+        assert!(verify_time_in_force(&order).is_ok());
+
+        let order = order_with_state(TimeInForce::ImmediateOrCancel, Status::Failed, "0");
+        assert!(verify_time_in_force(&order).is_err());
+
+        let order = order_with_state(TimeInForce::ImmediateOrCancel, Status::Open, "0");
+        assert!(verify_time_in_force(&order).is_err());
+    }
+
+    #[test]
+    fn test_verify_time_in_force_other() {
+        let order = order_with_state(TimeInForce::GoodUntilCancelled, Status::Filled, "1.5");
+        assert!(verify_time_in_force(&order).is_err());
+    }
+
+    #[test]
+    fn test_slippage_bps() {
+        let reference_price = BigDecimal::from(100);
+
+        let mut order = order_with_state(TimeInForce::GoodUntilCancelled, Status::Filled, "1");
+        order.average_filled_price = "101".to_string();
+        order.side = OrderSide::Buy;
+        let slippage = slippage_bps(&order, &reference_price).unwrap();
+        assert_eq!(slippage, BigDecimal::from(100));
+
+        order.side = OrderSide::Sell;
+        let slippage = slippage_bps(&order, &reference_price).unwrap();
+        assert_eq!(slippage, BigDecimal::from(-100));
+
+        order.average_filled_price = "99.5".to_string();
+        let slippage = slippage_bps(&order, &reference_price).unwrap();
+        assert_eq!(slippage, BigDecimal::from(50));
+    }
+
+    #[test]
+    fn test_slippage_bps_not_filled() {
+        let order = order_with_state(TimeInForce::GoodUntilCancelled, Status::Open, "0");
+        assert!(slippage_bps(&order, &BigDecimal::from(100)).is_err());
+    }
+
+    #[test]
+    fn test_create_order_edit_serialize() {
+        let edit = create_order_edit("1111-000000-000000", Some(5000.5), None).unwrap();
+        let json = serde_json::to_value(&edit).unwrap();
+        assert_eq!(json["order_id"], "1111-000000-000000");
+        let price = BigDecimal::from_str(json["price"].as_str().unwrap()).unwrap();
+        assert_eq!(price, BigDecimal::from_str("5000.5").unwrap());
+        assert!(json.get("size").is_none());
+
+        assert!(create_order_edit("1111-000000-000000", None, None).is_err());
+        assert!(create_order_edit("1111-000000-000000", None, Some(f64::NAN)).is_err());
+    }
+
+    #[test]
+    fn test_edit_order_response_deserialize() {
+        let input = r##"{
+            "success": false,
+            "errors": [
+                {
+                    "edit_failure_reason": "ONLY_LIMIT_ORDER_EDITS_SUPPORTED",
+                    "preview_failure_reason": "UNKNOWN_PREVIEW_FAILURE_REASON"
+                }
+            ]
+        }"##;
+        let result: EditOrderResponse = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            result.errors[0].edit_failure_reason,
+            Some(EditOrderFailureReason::OnlyLimitOrderEditsSupported)
+        );
+        assert!(result.warnings.is_none());
+    }
+
+    #[test]
+    fn test_preview_order_serialize() {
+        let order = create_market_order("BTC-USD", OrderSide::Buy, 10.0).unwrap();
+        let json = serde_json::to_value(order.as_preview()).unwrap();
+        assert!(json.get("client_order_id").is_none());
+        assert_eq!(json["product_id"], "BTC-USD");
+        assert_eq!(json["side"], "BUY");
+        assert!(json["order_configuration"]["market_market_ioc"].is_object());
+    }
+
+    #[test]
+    fn test_preview_order_response_deserialize() {
+        let input = r##"{
+            "order_total": "10.06",
+            "commission_total": "0.06",
+            "errs": ["PREVIEW_INSUFFICIENT_FUND"],
+            "warnings": [],
+            "quote_size": "10",
+            "base_size": "0.00034",
+            "best_bid": "29300.01",
+            "best_ask": "29300.02",
+            "is_max": false,
+            "slippage": "0.0001"
+        }"##;
+        let result: PreviewOrderResponse = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(
+            result.errors,
+            vec![PreviewCreateOrderFailureReason::PreviewInsufficientFund]
+        );
+        assert_eq!(
+            result.commission_total,
+            BigDecimal::from_str("0.06").unwrap()
+        );
+        assert!(!result.is_max);
+    }
+
     #[test]
     fn test_cancel_orders_response_serde() {
         let input = r##"{
@@ -1085,4 +2524,59 @@ mod tests {
         let result = &results.results[0];
         assert!(!result.success);
     }
+
+    #[test]
+    fn test_cancel_batches() {
+        let mut orders: Vec<Order> = (0..250)
+            .map(|i| {
+                let mut order =
+                    order_with_state(TimeInForce::GoodUntilCancelled, Status::Open, "0");
+                order.order_id = format!("order-{}", i);
+                order
+            })
+            .collect();
+        orders[0].product_id = "ETH-USD".to_string();
+        orders[1].status = Status::Cancelled;
+
+        let batches = cancel_batches(&orders, Some("BTC-USD"));
+        assert_eq!(
+            batches.iter().map(|batch| batch.len()).collect::<Vec<_>>(),
+            vec![100, 100, 48]
+        );
+        assert_eq!(batches[0][0], "order-2");
+        assert_eq!(batches[2][47], "order-249");
+
+        assert!(cancel_batches(&orders, Some("SOL-USD")).is_empty());
+
+        let batches = cancel_batches(&orders, None);
+        assert_eq!(
+            batches.iter().map(|batch| batch.len()).collect::<Vec<_>>(),
+            vec![100, 100, 49]
+        );
+        assert_eq!(batches[0][0], "order-0");
+    }
+
+    #[test]
+    fn test_order_decimal_accessors() {
+        let mut order = order_with_state(TimeInForce::GoodUntilCancelled, Status::Filled, "1.5");
+        order.average_filled_price = "20000.25".to_string();
+        assert_eq!(
+            order.filled_size().unwrap(),
+            BigDecimal::from_str("1.5").unwrap()
+        );
+        assert_eq!(
+            order.average_filled_price().unwrap(),
+            BigDecimal::from_str("20000.25").unwrap()
+        );
+        assert_eq!(order.total_fees().unwrap(), BigDecimal::from(0));
+        assert_eq!(
+            order.outstanding_hold_amount().unwrap(),
+            BigDecimal::from(0)
+        );
+
+        // "fee" is empty in the sample order.
+        assert!(order.fee().is_err());
+        order.filled_value = "garbage".to_string();
+        assert!(order.filled_value().is_err());
+    }
 }
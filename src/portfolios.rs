@@ -0,0 +1,305 @@
+//! Structures & Enums representing Coinbase's portfolio related structures
+
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use serde_derive::{Deserialize, Serialize};
+use serde_enum_str::{Deserialize_enum_str, Serialize_enum_str};
+use uuid::Uuid;
+
+use crate::accounts::Balance;
+use crate::orders::f64_to_valid_bigdecimal;
+
+/// Enum representing the possible types of portfolio
+#[derive(Deserialize_enum_str, Serialize_enum_str, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PortfolioType {
+    Undefined,
+    Default,
+    Consumer,
+    Intx,
+}
+
+/// Structure representing Coinbase's portfolio
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Portfolio {
+    /// Name of the portfolio.
+    pub name: String,
+    /// Unique identifier for the portfolio.
+    pub uuid: Uuid,
+    pub r#type: PortfolioType,
+    /// Whether the portfolio has been deleted.
+    pub deleted: bool,
+}
+
+#[doc(hidden)]
+/// Structure representing Coinbase's wrapper response for multiple portfolios
+#[derive(Deserialize, Debug)]
+pub struct PortfoliosResponse {
+    pub portfolios: Vec<Portfolio>,
+}
+
+#[doc(hidden)]
+/// Structure representing Coinbase's wrapper response for a single portfolio
+#[derive(Deserialize, Debug)]
+pub struct PortfolioResponse {
+    pub portfolio: Portfolio,
+}
+
+/// Structure representing the balances of a portfolio, in the user's native currency
+#[derive(Deserialize, Debug)]
+pub struct PortfolioBalances {
+    pub total_balance: Balance,
+    pub total_futures_balance: Balance,
+    pub total_cash_equivalent_balance: Balance,
+    pub total_crypto_balance: Balance,
+    pub futures_unrealized_pnl: Balance,
+    pub perp_unrealized_pnl: Balance,
+}
+
+/// Structure representing a spot position of a portfolio
+#[derive(Deserialize, Debug)]
+pub struct SpotPosition {
+    /// Currency of the position, e.g. BTC.
+    pub asset: String,
+    pub account_uuid: Uuid,
+    /// Value of the position, in the user's native currency.
+    pub total_balance_fiat: BigDecimal,
+    /// Size of the position, in `asset`.
+    pub total_balance_crypto: BigDecimal,
+    pub available_to_trade_fiat: BigDecimal,
+    /// Share of the portfolio's value, between 0 and 1.
+    pub allocation: BigDecimal,
+    pub one_day_change: BigDecimal,
+    pub cost_basis: Balance,
+    /// Whether the asset is cash or a cash equivalent.
+    pub is_cash: bool,
+}
+
+/// Structure representing a perpetual future position of a portfolio
+#[derive(Deserialize, Debug)]
+pub struct PerpPosition {
+    pub product_id: String,
+    pub product_uuid: Uuid,
+    pub symbol: String,
+    /// Signed size of the position: negative when short.
+    pub net_size: BigDecimal,
+    pub buy_order_size: BigDecimal,
+    pub sell_order_size: BigDecimal,
+    pub leverage: BigDecimal,
+}
+
+/// Structure representing the breakdown of a portfolio: balances and positions
+#[derive(Deserialize, Debug)]
+pub struct PortfolioBreakdown {
+    pub portfolio: Portfolio,
+    pub portfolio_balances: PortfolioBalances,
+    pub spot_positions: Vec<SpotPosition>,
+    /// Absent for portfolios without access to perpetual futures.
+    pub perp_positions: Option<Vec<PerpPosition>>,
+}
+
+#[doc(hidden)]
+/// Structure representing Coinbase's wrapper response for a portfolio breakdown
+#[derive(Deserialize, Debug)]
+pub struct PortfolioBreakdownResponse {
+    pub breakdown: PortfolioBreakdown,
+}
+
+/// Structure to fill to move funds between portfolios, to be sent to CB
+#[derive(Serialize, Debug)]
+pub struct MoveFunds {
+    funds: Balance,
+    source_portfolio_uuid: Uuid,
+    target_portfolio_uuid: Uuid,
+}
+
+/// Structure representing CB's response to a move funds request
+#[derive(Deserialize, Debug)]
+pub struct MoveFundsResponse {
+    pub source_portfolio_uuid: Uuid,
+    pub target_portfolio_uuid: Uuid,
+}
+
+/// Create a request moving `amount` of `currency` from the `source` portfolio to the `target` one.
+pub fn create_move_funds(
+    source: Uuid,
+    target: Uuid,
+    amount: f64,
+    currency: &str,
+) -> Result<MoveFunds> {
+    Ok(MoveFunds {
+        funds: Balance {
+            value: f64_to_valid_bigdecimal(amount)?,
+            currency: currency.to_string(),
+        },
+        source_portfolio_uuid: source,
+        target_portfolio_uuid: target,
+    })
+}
+
+//=========== TESTS ===========================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_portfolios_deserialize() {
+        let input = r##"{
+            "portfolios": [
+                {
+                    "name": "Default",
+                    "uuid": "8a2ac7ea-3c84-4fb3-9e55-0b9c1e2f7a11",
+                    "type": "DEFAULT",
+                    "deleted": false
+                },
+                {
+                    "name": "Grid bot",
+                    "uuid": "1f3c0b61-9a3e-4d1e-8f0a-2b6d7c8e9f00",
+                    "type": "CONSUMER",
+                    "deleted": true
+                }
+            ]
+        }"##;
+        let response: PortfoliosResponse = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(response.portfolios.len(), 2);
+        assert_eq!(response.portfolios[0].r#type, PortfolioType::Default);
+        assert_eq!(
+            response.portfolios[1].uuid.to_string(),
+            "1f3c0b61-9a3e-4d1e-8f0a-2b6d7c8e9f00"
+        );
+        assert!(response.portfolios[1].deleted);
+    }
+
+    #[test]
+    fn test_portfolio_type_deserialize() {
+        let input = r##""UNDEFINED""##;
+        let result: PortfolioType = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(result, PortfolioType::Undefined);
+
+        let input = r##""INTX""##;
+        let result: PortfolioType = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(result, PortfolioType::Intx);
+    }
+
+    #[test]
+    fn test_portfolio_type_serialize() {
+        let expected = r##""CONSUMER""##;
+        assert_eq!(
+            expected,
+            serde_json::to_string(&PortfolioType::Consumer).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_portfolio_response_deserialize() {
+        let input = r##"{
+            "portfolio": {
+                "name": "Grid bot",
+                "uuid": "1f3c0b61-9a3e-4d1e-8f0a-2b6d7c8e9f00",
+                "type": "CONSUMER",
+                "deleted": false
+            }
+        }"##;
+        let response: PortfolioResponse = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(response.portfolio.name, "Grid bot");
+        assert_eq!(response.portfolio.r#type, PortfolioType::Consumer);
+    }
+
+    const BREAKDOWN: &str = r##"{
+        "breakdown": {
+            "portfolio": {
+                "name": "Default",
+                "uuid": "8a2ac7ea-3c84-4fb3-9e55-0b9c1e2f7a11",
+                "type": "DEFAULT",
+                "deleted": false
+            },
+            "portfolio_balances": {
+                "total_balance": { "value": "1250.42", "currency": "USD" },
+                "total_futures_balance": { "value": "0", "currency": "USD" },
+                "total_cash_equivalent_balance": { "value": "250.42", "currency": "USD" },
+                "total_crypto_balance": { "value": "1000", "currency": "USD" },
+                "futures_unrealized_pnl": { "value": "0", "currency": "USD" },
+                "perp_unrealized_pnl": { "value": "0", "currency": "USD" }
+            },
+            "spot_positions": [
+                {
+                    "asset": "BTC",
+                    "account_uuid": "9dd482e4-d8ce-46f7-a261-281843bd2855",
+                    "total_balance_fiat": 1000,
+                    "total_balance_crypto": 0.0334,
+                    "available_to_trade_fiat": 1000,
+                    "allocation": 0.7997,
+                    "one_day_change": -0.0123,
+                    "cost_basis": { "value": "950.1", "currency": "USD" },
+                    "asset_img_url": "",
+                    "is_cash": false
+                }
+            ]
+        }
+    }"##;
+
+    #[test]
+    fn test_portfolio_breakdown_deserialize() {
+        let response: PortfolioBreakdownResponse =
+            serde_json::from_slice(BREAKDOWN.as_bytes()).unwrap();
+        let breakdown = response.breakdown;
+        assert_eq!(breakdown.portfolio.name, "Default");
+        assert_eq!(
+            breakdown.portfolio_balances.total_balance.value,
+            BigDecimal::from_str("1250.42").unwrap()
+        );
+        assert_eq!(breakdown.spot_positions.len(), 1);
+        assert_eq!(breakdown.spot_positions[0].asset, "BTC");
+        assert_eq!(
+            breakdown.spot_positions[0].total_balance_crypto,
+            BigDecimal::from_str("0.0334").unwrap()
+        );
+        assert!(breakdown.perp_positions.is_none());
+    }
+
+    #[test]
+    fn test_perp_position_deserialize() {
+        let input = r##"{
+            "product_id": "BTC-PERP-INTX",
+            "product_uuid": "cd34c18b-3665-4ed8-9305-3db277c49fc5",
+            "symbol": "BTC-PERP-INTX",
+            "net_size": "-0.01",
+            "buy_order_size": "0",
+            "sell_order_size": "0.02",
+            "leverage": "2.5"
+        }"##;
+        let position: PerpPosition = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(position.net_size, BigDecimal::from_str("-0.01").unwrap());
+        assert_eq!(position.leverage, BigDecimal::from_str("2.5").unwrap());
+    }
+
+    #[test]
+    fn test_move_funds_serialize() {
+        let source = Uuid::from_str("8a2ac7ea-3c84-4fb3-9e55-0b9c1e2f7a11").unwrap();
+        let target = Uuid::from_str("1f3c0b61-9a3e-4d1e-8f0a-2b6d7c8e9f00").unwrap();
+        let move_funds = create_move_funds(source, target, 250.0, "USD").unwrap();
+        let result = serde_json::to_value(&move_funds).unwrap();
+        assert_eq!(result["source_portfolio_uuid"], source.to_string());
+        assert_eq!(result["target_portfolio_uuid"], target.to_string());
+        assert_eq!(result["funds"]["currency"], "USD");
+        let value = BigDecimal::from_str(result["funds"]["value"].as_str().unwrap()).unwrap();
+        assert_eq!(value, BigDecimal::from(250));
+
+        assert!(create_move_funds(source, target, f64::NAN, "USD").is_err());
+    }
+
+    #[test]
+    fn test_move_funds_response_deserialize() {
+        let input = r##"{
+            "source_portfolio_uuid": "8a2ac7ea-3c84-4fb3-9e55-0b9c1e2f7a11",
+            "target_portfolio_uuid": "1f3c0b61-9a3e-4d1e-8f0a-2b6d7c8e9f00"
+        }"##;
+        let response: MoveFundsResponse = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(
+            response.target_portfolio_uuid.to_string(),
+            "1f3c0b61-9a3e-4d1e-8f0a-2b6d7c8e9f00"
+        );
+    }
+}
@@ -1,14 +1,18 @@
 //! Structures & Enums representing Coinbase's order related structures
 
+use anyhow::{anyhow, Result};
 use bigdecimal::BigDecimal;
-use serde::{Deserialize, Deserializer};
+use chrono::TimeZone;
+use serde::{Deserialize, Serialize};
 use serde_enum_str::{Deserialize_enum_str, Serialize_enum_str};
-use std::str::FromStr;
+use std::collections::HashSet;
 
+use crate::orders::OrderToSend;
+use crate::utils::deserialize_optional_bigdecimal;
 use crate::DateTime;
 
 /// Structure representing Coinbase's response for a pricebook
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Pricebook {
     pub product_id: String,
     pub bids: Vec<Bid>,
@@ -16,22 +20,44 @@ pub struct Pricebook {
     pub time: DateTime,
 }
 
+impl Pricebook {
+    /// Bid with the highest price, if any.
+    pub fn best_bid(&self) -> Option<&Bid> {
+        self.bids.iter().max_by(|a, b| a.price.cmp(&b.price))
+    }
+
+    /// Ask with the lowest price, if any.
+    pub fn best_ask(&self) -> Option<&Ask> {
+        self.asks.iter().min_by(|a, b| a.price.cmp(&b.price))
+    }
+
+    /// Best ask price minus best bid price. `None` if either side of the book is empty.
+    pub fn spread(&self) -> Option<BigDecimal> {
+        Some(&self.best_ask()?.price - &self.best_bid()?.price)
+    }
+
+    /// Average of the best bid and best ask prices. `None` if either side of the book is empty.
+    pub fn mid_price(&self) -> Option<BigDecimal> {
+        Some((&self.best_ask()?.price + &self.best_bid()?.price) / BigDecimal::from(2))
+    }
+}
+
 /// Structure representing Coinbase's response for a bid
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Bid {
     pub price: BigDecimal,
     pub size: BigDecimal,
 }
 
 /// Structure representing Coinbase's response for a ask
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Ask {
     pub price: BigDecimal,
     pub size: BigDecimal,
 }
 
 /// Structure representing Coinbase's response for a details of a fcm trading session
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct FcmTradingSessionDetails {
     pub is_session_open: bool,
     pub open_time: DateTime,
@@ -39,15 +65,21 @@ pub struct FcmTradingSessionDetails {
 }
 
 /// Structure representing Coinbase's response for perpetual details
-#[derive(Deserialize, Debug)]
+///
+/// Only the current funding is exposed: the Advanced Trade API has no endpoint listing past
+/// funding payments, and fills are not tagged as funding, so the funding history of a position
+/// can not be retrieved through this crate.
+#[derive(Deserialize, Serialize, Debug)]
 pub struct PerpetualDetails {
     pub open_interest: String,
+    /// Current funding rate, empty if unknown.
     pub funding_rate: String,
+    /// Time the funding rate applies to.
     pub funding_time: Option<DateTime>,
 }
 
 /// Structure representing Coinbase's response for details of a future product
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct FutureProductDetails {
     pub venue: String,
     pub contract_code: String,
@@ -59,49 +91,29 @@ pub struct FutureProductDetails {
     pub contract_expiry_timezone: String,
     /// Short version of the group_description, eg "Nano BTC".
     pub group_short_description: String,
-    /// Possible values: [UNKNOWN_RISK_MANAGEMENT_TYPE, MANAGED_BY_FCM, MANAGED_BY_VENUE]
-    pub risk_managed_by: String,
-    /// Possible values: [UNKNOWN_CONTRACT_EXPIRY_TYPE, EXPIRING]
-    pub contract_expiry_type: String,
+    pub risk_managed_by: RiskManagementType,
+    pub contract_expiry_type: ContractExpiryType,
     pub perpetual_details: PerpetualDetails,
     pub contract_display_name: String,
 }
 
-// Accounting for the fact that when no data are available Coinbase return sometimes null sometimes
-// the empty string ""
-fn deserialize_bigdecimal_stable<'de, D>(deserializer: D) -> Result<Option<BigDecimal>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    String::deserialize(deserializer).map(|b| {
-        if b.is_empty() {
-            Ok(None)
-        } else {
-            match BigDecimal::from_str(&b) {
-                Ok(value) => Ok(Some(value)),
-                Err(_) => Ok(None),
-            }
-        }
-    })?
-}
-
 /// Structure representing Coinbase's response for a product
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Product {
     /// The trading pair.
     pub product_id: String,
-    #[serde(default, deserialize_with = "deserialize_bigdecimal_stable")]
+    #[serde(default, deserialize_with = "deserialize_optional_bigdecimal")]
     /// The current price for the product, in quote currency.
     pub price: Option<BigDecimal>,
-    #[serde(default, deserialize_with = "deserialize_bigdecimal_stable")]
+    #[serde(default, deserialize_with = "deserialize_optional_bigdecimal")]
     /// The amount the price of the product has changed, in percent, in the last 24 hours.
     pub price_percentage_change_24h: Option<BigDecimal>, // from the doc, there is a % sign at the
     // end of the string. from the response, this never happens. same for the next 2 values.
     /// The trading volume for the product in the last 24 hours.
-    #[serde(default, deserialize_with = "deserialize_bigdecimal_stable")]
+    #[serde(default, deserialize_with = "deserialize_optional_bigdecimal")]
     pub volume_24h: Option<BigDecimal>,
     /// The percentage amount the volume of the product has changed in the last 24 hours.
-    #[serde(default, deserialize_with = "deserialize_bigdecimal_stable")]
+    #[serde(default, deserialize_with = "deserialize_optional_bigdecimal")]
     pub volume_percentage_change_24h: Option<BigDecimal>,
     /// Minimum amount base value can be increased or decreased at once.
     pub base_increment: BigDecimal,
@@ -126,7 +138,7 @@ pub struct Product {
     /// Whether or not the product is 'new'.
     pub new: bool,
     /// Status of the product.
-    pub status: String,
+    pub status: ProductStatus,
     /// Whether or not orders of the product can only be cancelled, not placed or edited.          
     pub cancel_only: bool,
     /// Whether or not orders of the product can only be limit orders, not market orders.
@@ -137,15 +149,15 @@ pub struct Product {
     pub trading_disabled: bool,
     /// Whether or not the product is in auction mode.
     pub auction_mode: bool,
-    /// Possible values: [SPOT, FUTURE]
-    pub product_type: String,
+    pub product_type: ProductType,
     /// Symbol of the quote currency.
     pub quote_currency_id: String,
     /// Symbol of the base currency.
     pub base_currency_id: String,
-    /// The current midpoint of the bid-ask spread, in quote currency.
     pub fcm_trading_session_details: Option<FcmTradingSessionDetails>,
-    pub mid_market_price: String,
+    /// The current midpoint of the bid-ask spread, in quote currency.
+    #[serde(default, deserialize_with = "deserialize_optional_bigdecimal")]
+    pub mid_market_price: Option<BigDecimal>,
     /// Product id for the corresponding unified book.
     pub alias: String,
     /// Product ids that this product serves as an alias for.
@@ -161,6 +173,177 @@ pub struct Product {
     pub future_product_details: Option<FutureProductDetails>,
 }
 
+/// Accessors defaulting to zero for the market data Coinbase leaves empty, e.g. for new or
+/// delisted products.
+impl Product {
+    /// [`Product::price`], or zero if unknown.
+    pub fn price_or_zero(&self) -> BigDecimal {
+        self.price.clone().unwrap_or_default()
+    }
+
+    /// [`Product::volume_24h`], or zero if unknown.
+    pub fn volume_24h_or_zero(&self) -> BigDecimal {
+        self.volume_24h.clone().unwrap_or_default()
+    }
+
+    /// [`Product::price_percentage_change_24h`], or zero if unknown.
+    pub fn price_change_pct_24h(&self) -> BigDecimal {
+        self.price_percentage_change_24h.clone().unwrap_or_default()
+    }
+}
+
+impl Product {
+    /// Whether orders can currently be placed on the product: it is neither disabled, nor
+    /// disabled for trading, nor cancel only, nor view only.
+    pub fn is_tradable(&self) -> bool {
+        !(self.is_disabled || self.trading_disabled || self.cancel_only || self.view_only)
+    }
+}
+
+/// Checks against the product's increments and size limits, to catch orders Coinbase would
+/// reject, e.g. with `INVALID_SIZE_PRECISION`, before sending them.
+impl Product {
+    /// Truncate `size` toward zero to a multiple of [`Product::base_increment`].
+    pub fn round_base_size(&self, size: BigDecimal) -> BigDecimal {
+        truncate_to_increment(size, &self.base_increment)
+    }
+
+    /// Truncate `price` toward zero to a multiple of [`Product::price_increment`].
+    pub fn round_price(&self, price: BigDecimal) -> BigDecimal {
+        truncate_to_increment(price, &self.price_increment)
+    }
+
+    /// Check that `order` is for this product, that its sizes and prices are multiples of the
+    /// product's increments, and that its sizes are within the product's min and max sizes.
+    ///
+    /// ```no_run
+    /// # use coinbase_v3::{basic_oauth, client, orders};
+    /// # use tokio_test;
+    /// # tokio_test::block_on(async {
+    /// # let oauth_cb_client = basic_oauth::OAuthCbClient::new("", "", "").unwrap();
+    /// # let cb_client = client::CbClient::new(&oauth_cb_client);
+    /// let product = cb_client.get_product("BTC-USD").await.unwrap();
+    /// let order = orders::create_limit_order_good_til_canceled(
+    ///     "BTC-USD",
+    ///     orders::OrderSide::Buy,
+    ///     0.001,
+    ///     25000.0,
+    ///     true,
+    /// )
+    /// .unwrap();
+    /// product.validate_order(&order).unwrap();
+    /// # });
+    /// ```
+    pub fn validate_order(&self, order: &OrderToSend) -> Result<()> {
+        anyhow::ensure!(
+            order.product_id() == self.product_id,
+            "Order for {} checked against product {}",
+            order.product_id(),
+            self.product_id
+        );
+
+        let config = order.order_configuration();
+        let mut base_size = None;
+        let mut quote_size = None;
+        let mut prices = Vec::new();
+        if let Some(market) = &config.market_market_ioc {
+            base_size = market.base_size.as_ref();
+            quote_size = market.quote_size.as_ref();
+        }
+        if let Some(sor_limit_ioc) = &config.sor_limit_ioc {
+            base_size = Some(&sor_limit_ioc.base_size);
+            prices.push(("limit_price", &sor_limit_ioc.limit_price));
+        }
+        for limit in [&config.limit_limit_gtc, &config.limit_limit_gtd]
+            .into_iter()
+            .flatten()
+        {
+            base_size = Some(&limit.base_size);
+            prices.push(("limit_price", &limit.limit_price));
+        }
+        for stop_limit in [
+            &config.stop_limit_stop_limit_gtc,
+            &config.stop_limit_stop_limit_gtd,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            base_size = Some(&stop_limit.base_size);
+            prices.push(("limit_price", &stop_limit.limit_price));
+            prices.push(("stop_price", &stop_limit.stop_price));
+        }
+        for trigger_bracket in [&config.trigger_bracket_gtc, &config.trigger_bracket_gtd]
+            .into_iter()
+            .flatten()
+        {
+            base_size = Some(&trigger_bracket.base_size);
+            prices.push(("limit_price", &trigger_bracket.limit_price));
+            prices.push(("stop_trigger_price", &trigger_bracket.stop_trigger_price));
+        }
+
+        if let Some(size) = base_size {
+            check_increment("base_size", size, "base_increment", &self.base_increment)?;
+            check_size_range("base_size", size, &self.base_min_size, &self.base_max_size)?;
+        }
+        if let Some(size) = quote_size {
+            check_increment("quote_size", size, "quote_increment", &self.quote_increment)?;
+            check_size_range(
+                "quote_size",
+                size,
+                &self.quote_min_size,
+                &self.quote_max_size,
+            )?;
+        }
+        for (name, price) in prices {
+            check_increment(name, price, "price_increment", &self.price_increment)?;
+        }
+        Ok(())
+    }
+}
+
+/// Truncate `value` toward zero to a multiple of `increment`, unless `increment` is not positive.
+fn truncate_to_increment(value: BigDecimal, increment: &BigDecimal) -> BigDecimal {
+    if *increment <= BigDecimal::from(0) {
+        return value;
+    }
+    let remainder = &value % increment;
+    value - remainder
+}
+
+fn check_increment(
+    name: &str,
+    value: &BigDecimal,
+    increment_name: &str,
+    increment: &BigDecimal,
+) -> Result<()> {
+    anyhow::ensure!(
+        *increment <= BigDecimal::from(0) || (value % increment) == BigDecimal::from(0),
+        "{} {} is not a multiple of the product's {} {}",
+        name,
+        value,
+        increment_name,
+        increment
+    );
+    Ok(())
+}
+
+fn check_size_range(
+    name: &str,
+    value: &BigDecimal,
+    min: &BigDecimal,
+    max: &BigDecimal,
+) -> Result<()> {
+    anyhow::ensure!(
+        value >= min && value <= max,
+        "{} {} is out of the product's range [{}, {}]",
+        name,
+        value,
+        min,
+        max
+    );
+    Ok(())
+}
+
 #[doc(hidden)]
 #[derive(Deserialize, Debug)]
 pub struct ProductsResponse {
@@ -184,16 +367,48 @@ pub struct PricebookResponse {
 #[derive(Deserialize_enum_str, Serialize_enum_str, Debug, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ProductType {
+    UnknownProductType,
     Spot,
     Future,
+    /// Any value not known by this crate.
+    #[serde(other)]
+    Unknown(String),
 }
 
 /// Enum representing Coinbase's valid contract expiry types
 #[derive(Deserialize_enum_str, Serialize_enum_str, Debug, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ContractExpiryType {
-    UnknownRiskManagementType,
+    UnknownContractExpiryType,
     Expiring,
+    Perpetual,
+    /// Any value not known by this crate.
+    #[serde(other)]
+    Unknown(String),
+}
+
+/// Enum representing Coinbase's valid product statuses
+#[derive(Deserialize_enum_str, Serialize_enum_str, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProductStatus {
+    Online,
+    Offline,
+    Delisted,
+    /// Any value not known by this crate.
+    #[serde(other)]
+    Other(String),
+}
+
+/// Enum representing Coinbase's valid risk management types (for futures)
+#[derive(Deserialize_enum_str, Serialize_enum_str, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RiskManagementType {
+    UnknownRiskManagementType,
+    ManagedByFcm,
+    ManagedByVenue,
+    /// Any value not known by this crate.
+    #[serde(other)]
+    Unknown(String),
 }
 
 /// Enum representing Coinbase's valid Granularities (for candles)
@@ -211,8 +426,43 @@ pub enum Granularity {
     OneDay,
 }
 
+impl Granularity {
+    /// Duration of a candle bucket, in seconds. `None` for `UnknownGranularity`.
+    pub fn to_seconds(&self) -> Option<i64> {
+        match self {
+            Granularity::UnknownGranularity => None,
+            Granularity::OneMinute => Some(60),
+            Granularity::FiveMinute => Some(300),
+            Granularity::FifteenMinute => Some(900),
+            Granularity::ThirtyMinute => Some(1800),
+            Granularity::OneHour => Some(3600),
+            Granularity::TwoHour => Some(7200),
+            Granularity::SixHour => Some(21600),
+            Granularity::OneDay => Some(86400),
+        }
+    }
+
+    /// Granularity matching a bucket duration in seconds, if Coinbase supports it.
+    pub fn from_seconds(seconds: i64) -> Option<Granularity> {
+        match seconds {
+            60 => Some(Granularity::OneMinute),
+            300 => Some(Granularity::FiveMinute),
+            900 => Some(Granularity::FifteenMinute),
+            1800 => Some(Granularity::ThirtyMinute),
+            3600 => Some(Granularity::OneHour),
+            7200 => Some(Granularity::TwoHour),
+            21600 => Some(Granularity::SixHour),
+            86400 => Some(Granularity::OneDay),
+            _ => None,
+        }
+    }
+}
+
+/// Maximum number of candles returned by Coinbase for a single request.
+pub const MAX_CANDLES_PER_REQUEST: i64 = 300;
+
 /// Structure representing Coinbase's response for a candle
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Candle {
     /// Timestamp for bucket start time, in UNIX time.
     pub start: String,
@@ -228,6 +478,20 @@ pub struct Candle {
     pub volume: BigDecimal,
 }
 
+impl Candle {
+    /// Bucket start time, parsed from the UNIX timestamp stored in `start`.
+    pub fn start_time(&self) -> Result<DateTime> {
+        let seconds = self
+            .start
+            .parse::<i64>()
+            .map_err(|err| anyhow!("Invalid candle start {:?}: {}", self.start, err))?;
+        chrono::Utc
+            .timestamp_opt(seconds, 0)
+            .single()
+            .ok_or(anyhow!("Candle start out of range: {}", seconds))
+    }
+}
+
 #[doc(hidden)]
 #[derive(Deserialize, Debug)]
 pub struct CandlesResponse {
@@ -245,7 +509,7 @@ pub enum Side {
     Sell,
 }
 /// Structure representing Coinbase's response for a Trade
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct Trade {
     /// The ID of the trade that was placed.
     pub trade_id: String,
@@ -259,11 +523,12 @@ pub struct Trade {
     pub time: DateTime,
     /// Possible values: [UNKNOWN_ORDER_SIDE, BUY, SELL]
     pub side: Side,
-    // bid and ask are Strings as CB response returns "" for them...
     /// The best bid for the `product_id`, in quote currency.
-    pub bid: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_bigdecimal")]
+    pub bid: Option<BigDecimal>,
     /// The best ask for the `product_id`, in quote currency.
-    pub ask: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_bigdecimal")]
+    pub ask: Option<BigDecimal>,
 }
 
 /// Structure representing Coinbase's response listing multiple Market Trades
@@ -286,11 +551,282 @@ pub enum TradeType {
     Synthetic,
 }
 
+/// Keep the `top_n` products with the largest 24h volume, sorted by descending volume.
+///
+/// Products without a known 24h volume are skipped. Products with equal volumes keep their
+/// original relative order.
+pub fn top_by_volume(products: Vec<Product>, top_n: usize) -> Vec<Product> {
+    let mut products: Vec<Product> = products
+        .into_iter()
+        .filter(|product| product.volume_24h.is_some())
+        .collect();
+    products.sort_by(|a, b| b.volume_24h.cmp(&a.volume_24h));
+    products.truncate(top_n);
+    products
+}
+
+/// Keep the products which orders can currently be placed on.
+///
+/// See [`Product::is_tradable`].
+pub fn tradable_only(products: Vec<Product>) -> Vec<Product> {
+    products.into_iter().filter(Product::is_tradable).collect()
+}
+
+/// Collect the distinct quote currencies (e.g. USD, USDC, BTC) of the given products.
+pub fn quote_currencies(products: &[Product]) -> HashSet<String> {
+    products
+        .iter()
+        .map(|product| product.quote_currency_id.clone())
+        .collect()
+}
+
+/// Collect the distinct base currencies of the given products.
+pub fn base_currencies(products: &[Product]) -> HashSet<String> {
+    products
+        .iter()
+        .map(|product| product.base_currency_id.clone())
+        .collect()
+}
+
+/// Split `[start, end]` into consecutive windows spanning at most [`MAX_CANDLES_PER_REQUEST`]
+/// candles of the given granularity.
+///
+/// Consecutive windows share their boundary, so the candle starting there may be returned twice.
+pub fn candle_windows(
+    start: &DateTime,
+    end: &DateTime,
+    granularity: &Granularity,
+) -> Result<Vec<(DateTime, DateTime)>> {
+    let seconds = granularity
+        .to_seconds()
+        .ok_or(anyhow!("Invalid granularity: {:?}", granularity))?;
+    let window = chrono::Duration::seconds(MAX_CANDLES_PER_REQUEST * seconds);
+
+    let mut windows = Vec::new();
+    let mut window_start = *start;
+    loop {
+        let window_end = std::cmp::min(window_start + window, *end);
+        windows.push((window_start, window_end));
+        if window_end >= *end {
+            break;
+        }
+        window_start = window_end;
+    }
+    Ok(windows)
+}
+
+/// Merge candles fetched over several windows: sort them in chronological order and drop the
+/// duplicates found at window boundaries.
+pub fn merge_candles(mut candles: Vec<Candle>) -> Vec<Candle> {
+    candles.sort_by_key(|candle| candle.start.parse::<i64>().ok());
+    candles.dedup_by(|a, b| a.start == b.start);
+    candles
+}
+
+/// Estimate the total quote cost of a market order of `base_size` on `side`, by walking the
+/// levels of `book` from the best price: asks for a buy, bids for a sell.
+///
+/// `None` if the book is not deep enough to fill `base_size`, or for an unknown side.
+pub fn estimate_market_fill(
+    book: &Pricebook,
+    side: &Side,
+    base_size: BigDecimal,
+) -> Option<BigDecimal> {
+    let mut levels: Vec<(&BigDecimal, &BigDecimal)> = match side {
+        Side::Buy => book
+            .asks
+            .iter()
+            .map(|ask| (&ask.price, &ask.size))
+            .collect(),
+        Side::Sell => book
+            .bids
+            .iter()
+            .map(|bid| (&bid.price, &bid.size))
+            .collect(),
+        Side::UnknownOrderSide => return None,
+    };
+    match side {
+        Side::Buy => levels.sort_by(|a, b| a.0.cmp(b.0)),
+        _ => levels.sort_by(|a, b| b.0.cmp(a.0)),
+    }
+
+    let zero = BigDecimal::from(0);
+    let mut remaining = base_size;
+    let mut cost = BigDecimal::from(0);
+    for (price, size) in levels {
+        if remaining <= zero {
+            break;
+        }
+        let filled = std::cmp::min(&remaining, size).clone();
+        cost += price * &filled;
+        remaining -= filled;
+    }
+    if remaining > zero {
+        None
+    } else {
+        Some(cost)
+    }
+}
+
+/// Sort candles in chronological order, as Coinbase may return them newest first.
+///
+/// Candles with an invalid `start` are moved first.
+pub fn sort_candles_chronological(candles: &mut [Candle]) {
+    candles.sort_by_key(|candle| candle.start.parse::<i64>().ok());
+}
+
+/// Check that chronologically sorted candles follow each other without gap, `granularity` apart.
+///
+/// Fails on the first gap, overlap or out of order candle found.
+pub fn validate_contiguous(candles: &[Candle], granularity: &Granularity) -> Result<()> {
+    let seconds = granularity
+        .to_seconds()
+        .ok_or(anyhow!("Invalid granularity: {:?}", granularity))?;
+    let starts: Vec<DateTime> = candles
+        .iter()
+        .map(|candle| candle.start_time())
+        .collect::<Result<_>>()?;
+    for pair in starts.windows(2) {
+        let step = (pair[1] - pair[0]).num_seconds();
+        if step != seconds {
+            return Err(anyhow!(
+                "Candles are not contiguous: {} followed by {}, expected {}s apart",
+                pair[0],
+                pair[1],
+                seconds
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Aggregate candles of granularity `from` into candles of the coarser granularity `to`, e.g. to
+/// build FIVE_MINUTE candles out of ONE_MINUTE ones.
+///
+/// The resampled candles are sorted in chronological order. Buckets start on multiples of `to`
+/// since the UNIX epoch, as Coinbase's do. Fails if `to` is not a multiple of `from`.
+pub fn resample(candles: &[Candle], from: &Granularity, to: &Granularity) -> Result<Vec<Candle>> {
+    let from_seconds = from
+        .to_seconds()
+        .ok_or(anyhow!("Invalid granularity: {:?}", from))?;
+    let to_seconds = to
+        .to_seconds()
+        .ok_or(anyhow!("Invalid granularity: {:?}", to))?;
+    if to_seconds % from_seconds != 0 {
+        return Err(anyhow!(
+            "Cannot resample {:?} candles to {:?}: not a multiple",
+            from,
+            to
+        ));
+    }
+
+    let mut sorted: Vec<(i64, &Candle)> = candles
+        .iter()
+        .map(|candle| Ok((candle.start_time()?.timestamp(), candle)))
+        .collect::<Result<_>>()?;
+    sorted.sort_by_key(|(start, _)| *start);
+
+    let mut resampled: Vec<Candle> = Vec::new();
+    let mut current_bucket = None;
+    for (start, candle) in sorted {
+        let bucket = start - start.rem_euclid(to_seconds);
+        match resampled.last_mut() {
+            Some(last) if current_bucket == Some(bucket) => {
+                last.high = std::cmp::max(&last.high, &candle.high).clone();
+                last.low = std::cmp::min(&last.low, &candle.low).clone();
+                last.close = candle.close.clone();
+                last.volume += &candle.volume;
+            }
+            _ => {
+                current_bucket = Some(bucket);
+                resampled.push(Candle {
+                    start: bucket.to_string(),
+                    low: candle.low.clone(),
+                    high: candle.high.clone(),
+                    open: candle.open.clone(),
+                    close: candle.close.clone(),
+                    volume: candle.volume.clone(),
+                });
+            }
+        }
+    }
+    Ok(resampled)
+}
+
 //=========== TESTS ===========================================================
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::assert_enum_round_trip;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_granularity_round_trip() {
+        assert_enum_round_trip(&[
+            (Granularity::UnknownGranularity, "UNKNOWN_GRANULARITY"),
+            (Granularity::OneMinute, "ONE_MINUTE"),
+            (Granularity::FiveMinute, "FIVE_MINUTE"),
+            (Granularity::FifteenMinute, "FIFTEEN_MINUTE"),
+            (Granularity::ThirtyMinute, "THIRTY_MINUTE"),
+            (Granularity::OneHour, "ONE_HOUR"),
+            (Granularity::TwoHour, "TWO_HOUR"),
+            (Granularity::SixHour, "SIX_HOUR"),
+            (Granularity::OneDay, "ONE_DAY"),
+        ]);
+    }
+
+    /// Build a spot product from a minimal response, overriding some of its fields.
+    fn product_with(product_id: &str, overrides: serde_json::Value) -> Product {
+        let mut input = serde_json::json!({
+            "product_id": product_id,
+            "price": "140.21",
+            "base_increment": "0.00000001",
+            "quote_increment": "0.01",
+            "quote_min_size": "1",
+            "quote_max_size": "10000000",
+            "base_min_size": "0.000016",
+            "base_max_size": "2600",
+            "base_name": "Bitcoin",
+            "quote_name": "US Dollar",
+            "watched": false,
+            "is_disabled": false,
+            "new": false,
+            "status": "online",
+            "cancel_only": false,
+            "limit_only": false,
+            "post_only": false,
+            "trading_disabled": false,
+            "auction_mode": false,
+            "product_type": "SPOT",
+            "quote_currency_id": "USD",
+            "base_currency_id": "BTC",
+            "mid_market_price": "",
+            "alias": "",
+            "alias_to": [],
+            "base_display_symbol": "BTC",
+            "quote_display_symbol": "USD",
+            "view_only": false,
+            "price_increment": "0.01"
+        });
+        for (key, value) in overrides.as_object().unwrap() {
+            input[key] = value.clone();
+        }
+        serde_json::from_value(input).unwrap()
+    }
+
+    #[test]
+    fn test_product_serde_round_trip() {
+        let product = product_with("BTC-USD", serde_json::json!({"mid_market_price": "140.2"}));
+        let json = serde_json::to_value(&product).unwrap();
+        let result: Product = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&result).unwrap(), json);
+        assert_eq!(
+            result.mid_market_price,
+            Some(BigDecimal::from_str("140.2").unwrap())
+        );
+    }
+
     #[test]
     fn test_product_deserialize() {
         let input = r##"{
@@ -330,7 +866,160 @@ mod tests {
         // "price_percentage_change_24h": "9", -- Removed to test Option
 
         let product: Product = serde_json::from_slice(input.as_bytes()).unwrap();
-        assert_eq!(product.product_type, "SPOT".to_string());
+        assert_eq!(product.product_type, ProductType::Spot);
+        assert_eq!(product.status, ProductStatus::Online);
+        assert_eq!(product.mid_market_price, None);
+        assert!(product.future_product_details.is_none());
+
+        assert_eq!(product.price_or_zero(), BigDecimal::from(0));
+        assert_eq!(product.volume_24h_or_zero(), BigDecimal::from(6));
+        assert_eq!(product.price_change_pct_24h(), BigDecimal::from(0));
+
+        let product = product_with(
+            "BTC-USD",
+            serde_json::json!({"price_percentage_change_24h": "-1.5"}),
+        );
+        assert_eq!(
+            product.price_or_zero(),
+            BigDecimal::from_str("140.21").unwrap()
+        );
+        assert_eq!(
+            product.price_change_pct_24h(),
+            BigDecimal::from_str("-1.5").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_round_to_increments() {
+        let product = product_with("BTC-USD", serde_json::json!({}));
+        assert_eq!(
+            product.round_base_size(BigDecimal::from_str("0.123456789").unwrap()),
+            BigDecimal::from_str("0.12345678").unwrap()
+        );
+        assert_eq!(
+            product.round_price(BigDecimal::from_str("25000.129").unwrap()),
+            BigDecimal::from_str("25000.12").unwrap()
+        );
+        assert_eq!(
+            product.round_price(BigDecimal::from(25000)),
+            BigDecimal::from(25000)
+        );
+    }
+
+    #[test]
+    fn test_validate_order() {
+        use crate::orders::{self, OrderSide, StopDirection};
+
+        let product = product_with("BTC-USD", serde_json::json!({}));
+        let valid_orders = [
+            orders::create_market_order("BTC-USD", OrderSide::Buy, 10.5).unwrap(),
+            orders::create_market_order("BTC-USD", OrderSide::Sell, 0.5).unwrap(),
+            orders::create_limit_order_good_til_canceled(
+                "BTC-USD",
+                OrderSide::Buy,
+                0.25,
+                25000.5,
+                true,
+            )
+            .unwrap(),
+            orders::create_stop_limit_order_good_til_canceled(
+                "BTC-USD",
+                OrderSide::Sell,
+                0.25,
+                24000.0,
+                24500.25,
+                StopDirection::StopDirectionStopDown,
+            )
+            .unwrap(),
+        ];
+        for order in &valid_orders {
+            assert!(product.validate_order(order).is_ok(), "{:?}", order);
+        }
+
+        let invalid_orders = [
+            // Other product
+            orders::create_market_order("ETH-USD", OrderSide::Buy, 10.0).unwrap(),
+            // Quote size precision
+            orders::create_market_order("BTC-USD", OrderSide::Buy, 10.005).unwrap(),
+            // Quote size below quote_min_size
+            orders::create_market_order("BTC-USD", OrderSide::Buy, 0.5).unwrap(),
+            // Base size precision
+            orders::create_market_order("BTC-USD", OrderSide::Sell, 0.000016001).unwrap(),
+            // Base size below base_min_size
+            orders::create_market_order("BTC-USD", OrderSide::Sell, 0.00001).unwrap(),
+            // Base size above base_max_size
+            orders::create_sor_limit_ioc_order("BTC-USD", OrderSide::Buy, 3000.0, 25000.0).unwrap(),
+            // Price precision
+            orders::create_limit_order_good_til_canceled(
+                "BTC-USD",
+                OrderSide::Buy,
+                0.25,
+                25000.125,
+                true,
+            )
+            .unwrap(),
+        ];
+        for order in &invalid_orders {
+            assert!(product.validate_order(order).is_err(), "{:?}", order);
+        }
+    }
+
+    #[test]
+    fn test_future_product_deserialize() {
+        let product = product_with(
+            "BIT-28JUL23-CDE",
+            serde_json::json!({
+                "product_type": "FUTURE",
+                "mid_market_price": "29412.5",
+                "fcm_trading_session_details": {
+                    "is_session_open": true,
+                    "open_time": "2023-07-06T22:00:00Z",
+                    "close_time": "2023-07-07T21:00:00Z"
+                },
+                "future_product_details": {
+                    "venue": "cde",
+                    "contract_code": "BIT",
+                    "contract_expiry": "2023-07-28T15:00:00Z",
+                    "contract_size": "0.01",
+                    "contract_root_unit": "BTC",
+                    "group_description": "Nano Bitcoin Futures",
+                    "contract_expiry_timezone": "Europe/London",
+                    "group_short_description": "Nano BTC",
+                    "risk_managed_by": "MANAGED_BY_FCM",
+                    "contract_expiry_type": "EXPIRING",
+                    "perpetual_details": {
+                        "open_interest": "",
+                        "funding_rate": "",
+                        "funding_time": null
+                    },
+                    "contract_display_name": "BTC 28 JUL 23"
+                }
+            }),
+        );
+        assert_eq!(product.product_type, ProductType::Future);
+        assert_eq!(
+            product.mid_market_price,
+            Some(BigDecimal::from_str("29412.5").unwrap())
+        );
+        assert!(product.fcm_trading_session_details.unwrap().is_session_open);
+        let details = product.future_product_details.unwrap();
+        assert_eq!(details.contract_expiry_type, ContractExpiryType::Expiring);
+        assert_eq!(details.risk_managed_by, RiskManagementType::ManagedByFcm);
+    }
+
+    #[test]
+    fn test_product_status_deserialize() {
+        let input = r##"["online", "offline", "delisted", "auction"]"##;
+        let result: Vec<ProductStatus> = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(
+            result,
+            [
+                ProductStatus::Online,
+                ProductStatus::Offline,
+                ProductStatus::Delisted,
+                ProductStatus::Other("auction".to_string()),
+            ]
+        );
     }
 
     #[test]
@@ -346,6 +1035,87 @@ mod tests {
         assert_eq!("QSP-USDT".to_string(), pricebook.product_id);
     }
 
+    #[test]
+    fn test_pricebook_spread_and_mid_price() {
+        let input = r##"{
+            "product_id": "QSP-USDT",
+            "bids": [{ "price": "0.01251", "size": "7448" }],
+            "asks": [{ "price": "0.0127", "size": "2850" }],
+            "time": "2023-07-05T05:30:57.651784Z"
+        }"##;
+        let pricebook: Pricebook = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(
+            pricebook.best_bid().unwrap().price,
+            BigDecimal::from_str("0.01251").unwrap()
+        );
+        assert_eq!(
+            pricebook.best_ask().unwrap().size,
+            BigDecimal::from_str("2850").unwrap()
+        );
+        assert_eq!(
+            pricebook.spread().unwrap(),
+            BigDecimal::from_str("0.00019").unwrap()
+        );
+        assert_eq!(
+            pricebook.mid_price().unwrap(),
+            BigDecimal::from_str("0.012605").unwrap()
+        );
+
+        let input = r##"{
+            "product_id": "QSP-USDT",
+            "bids": [{ "price": "0.01251", "size": "7448" }],
+            "asks": [],
+            "time": "2023-07-05T05:30:57.651784Z"
+        }"##;
+        let pricebook: Pricebook = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert!(pricebook.best_bid().is_some());
+        assert!(pricebook.best_ask().is_none());
+        assert!(pricebook.spread().is_none());
+        assert!(pricebook.mid_price().is_none());
+    }
+
+    #[test]
+    fn test_estimate_market_fill() {
+        let input = r##"{
+            "product_id": "BTC-USD",
+            "bids": [
+                { "price": "99", "size": "1" },
+                { "price": "98", "size": "2" },
+                { "price": "97", "size": "5" }
+            ],
+            "asks": [
+                { "price": "102", "size": "2" },
+                { "price": "101", "size": "1" },
+                { "price": "103", "size": "5" }
+            ],
+            "time": "2023-07-05T05:30:57.651784Z"
+        }"##;
+        let book: Pricebook = serde_json::from_slice(input.as_bytes()).unwrap();
+
+        // 1 @ 101 + 2 @ 102 + 0.5 @ 103
+        assert_eq!(
+            estimate_market_fill(&book, &Side::Buy, BigDecimal::from_str("3.5").unwrap()),
+            Some(BigDecimal::from_str("356.5").unwrap())
+        );
+        // 1 @ 99 + 1 @ 98
+        assert_eq!(
+            estimate_market_fill(&book, &Side::Sell, BigDecimal::from(2)),
+            Some(BigDecimal::from(197))
+        );
+        assert_eq!(
+            estimate_market_fill(&book, &Side::Sell, BigDecimal::from(8)),
+            Some(BigDecimal::from(780))
+        );
+        assert_eq!(
+            estimate_market_fill(&book, &Side::Sell, BigDecimal::from(9)),
+            None
+        );
+        assert_eq!(
+            estimate_market_fill(&book, &Side::UnknownOrderSide, BigDecimal::from(1)),
+            None
+        );
+    }
+
     #[test]
     fn test_product_type_deserialize() {
         let input = r##""SPOT""##;
@@ -355,6 +1125,14 @@ mod tests {
         let input = r##""FUTURE""##;
         let product_type: ProductType = serde_json::from_slice(input.as_bytes()).unwrap();
         assert_eq!(product_type, ProductType::Future);
+
+        let input = r##""UNKNOWN_PRODUCT_TYPE""##;
+        let product_type: ProductType = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(product_type, ProductType::UnknownProductType);
+
+        let input = r##""OPTION""##;
+        let product_type: ProductType = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(product_type, ProductType::Unknown("OPTION".to_string()));
     }
 
     #[test]
@@ -371,21 +1149,32 @@ mod tests {
 
     #[test]
     fn test_contract_expiry_type_deserialize() {
-        let input = r##""UNKNOWN_RISK_MANAGEMENT_TYPE""##;
+        let input = r##""UNKNOWN_CONTRACT_EXPIRY_TYPE""##;
         let expiry_type: ContractExpiryType = serde_json::from_slice(input.as_bytes()).unwrap();
-        assert_eq!(expiry_type, ContractExpiryType::UnknownRiskManagementType);
+        assert_eq!(expiry_type, ContractExpiryType::UnknownContractExpiryType);
 
         let input = r##""EXPIRING""##;
         let expiry_type: ContractExpiryType = serde_json::from_slice(input.as_bytes()).unwrap();
         assert_eq!(expiry_type, ContractExpiryType::Expiring);
+
+        let input = r##""PERPETUAL""##;
+        let expiry_type: ContractExpiryType = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(expiry_type, ContractExpiryType::Perpetual);
+
+        let input = r##""SOMETIMES""##;
+        let expiry_type: ContractExpiryType = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(
+            expiry_type,
+            ContractExpiryType::Unknown("SOMETIMES".to_string())
+        );
     }
 
     #[test]
     fn test_contract_expiry_type_serialize() {
-        let expected = r##""UNKNOWN_RISK_MANAGEMENT_TYPE""##;
+        let expected = r##""UNKNOWN_CONTRACT_EXPIRY_TYPE""##;
         assert_eq!(
             expected,
-            serde_json::to_string(&ContractExpiryType::UnknownRiskManagementType).unwrap()
+            serde_json::to_string(&ContractExpiryType::UnknownContractExpiryType).unwrap()
         );
 
         let expected = r##""EXPIRING""##;
@@ -439,6 +1228,32 @@ mod tests {
         }"##;
         let result: FutureProductDetails = serde_json::from_slice(input.as_bytes()).unwrap();
         assert_eq!(result.perpetual_details.open_interest, "string".to_string());
+        assert_eq!(
+            result.risk_managed_by,
+            RiskManagementType::UnknownRiskManagementType
+        );
+        assert_eq!(
+            result.contract_expiry_type,
+            ContractExpiryType::UnknownContractExpiryType
+        );
+    }
+
+    #[test]
+    fn test_risk_management_type_deserialize() {
+        let input = r##""MANAGED_BY_FCM""##;
+        let result: RiskManagementType = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(result, RiskManagementType::ManagedByFcm);
+
+        let input = r##""MANAGED_BY_VENUE""##;
+        let result: RiskManagementType = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(result, RiskManagementType::ManagedByVenue);
+
+        let input = r##""MANAGED_BY_SOMEONE""##;
+        let result: RiskManagementType = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(
+            result,
+            RiskManagementType::Unknown("MANAGED_BY_SOMEONE".to_string())
+        );
     }
 
     #[test]
@@ -540,6 +1355,25 @@ mod tests {
         }"##;
         let result: Trade = serde_json::from_slice(input.as_bytes()).unwrap();
         assert_eq!(result.product_id, "OGN-BTC".to_string());
+        assert_eq!(result.bid, None);
+        assert_eq!(result.ask, None);
+
+        let input = r##"{
+            "trade_id":"796313",
+            "product_id":"OGN-BTC",
+            "price":"0.00000318",
+            "size":"1.48",
+            "time":"2023-08-11T21:37:07.361937Z",
+            "side":"BUY",
+            "bid":"0.00000317",
+            "ask":null
+        }"##;
+        let result: Trade = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(
+            result.bid,
+            Some(BigDecimal::from_str("0.00000317").unwrap())
+        );
+        assert_eq!(result.ask, None);
     }
 
     #[test]
@@ -610,4 +1444,218 @@ mod tests {
         let expected = r##""FILL""##;
         assert_eq!(expected, serde_json::to_string(&TradeType::Fill).unwrap());
     }
+
+    #[test]
+    fn test_top_by_volume() {
+        let products = vec![
+            product_with("A-USD", serde_json::json!({"volume_24h": "10"})),
+            product_with("B-USD", serde_json::json!({"volume_24h": ""})),
+            product_with("C-USD", serde_json::json!({"volume_24h": "30.5"})),
+            product_with("D-USD", serde_json::json!({"volume_24h": "10"})),
+            product_with("E-USD", serde_json::json!({"volume_24h": "2"})),
+        ];
+        let top: Vec<String> = top_by_volume(products, 3)
+            .into_iter()
+            .map(|product| product.product_id)
+            .collect();
+        assert_eq!(top, vec!["C-USD", "A-USD", "D-USD"]);
+    }
+
+    #[test]
+    fn test_tradable_only() {
+        let products = vec![
+            product_with("A-USD", serde_json::json!({})),
+            product_with("B-USD", serde_json::json!({"is_disabled": true})),
+            product_with("C-USD", serde_json::json!({"trading_disabled": true})),
+            product_with("D-USD", serde_json::json!({"cancel_only": true})),
+            product_with("E-USD", serde_json::json!({"view_only": true})),
+            product_with(
+                "F-USD",
+                serde_json::json!({"limit_only": true, "post_only": true}),
+            ),
+        ];
+        let tradable: Vec<String> = tradable_only(products)
+            .into_iter()
+            .map(|product| product.product_id)
+            .collect();
+        assert_eq!(tradable, vec!["A-USD", "F-USD"]);
+    }
+
+    #[test]
+    fn test_quote_and_base_currencies() {
+        let products = vec![
+            product_with("BTC-USD", serde_json::json!({})),
+            product_with("ETH-USD", serde_json::json!({"base_currency_id": "ETH"})),
+            product_with(
+                "ETH-BTC",
+                serde_json::json!({"base_currency_id": "ETH", "quote_currency_id": "BTC"}),
+            ),
+        ];
+        let quotes = quote_currencies(&products);
+        assert_eq!(
+            quotes,
+            HashSet::from(["USD".to_string(), "BTC".to_string()])
+        );
+        let bases = base_currencies(&products);
+        assert_eq!(bases, HashSet::from(["BTC".to_string(), "ETH".to_string()]));
+    }
+
+    fn candle_at(start: i64) -> Candle {
+        let input = format!(
+            r##"{{"start": "{}", "low": "1", "high": "2", "open": "1.5", "close": "1.8", "volume": "10"}}"##,
+            start
+        );
+        serde_json::from_slice(input.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_candle_windows() {
+        let start = DateTime::from_str("2023-01-01T00:00:00Z").unwrap();
+        let end = start + chrono::Duration::minutes(1000);
+        let windows = candle_windows(&start, &end, &Granularity::OneMinute).unwrap();
+        assert_eq!(windows.len(), 4);
+        assert_eq!(windows[0], (start, start + chrono::Duration::minutes(300)));
+        assert_eq!(windows[1].0, windows[0].1);
+        assert_eq!(windows[3], (start + chrono::Duration::minutes(900), end));
+
+        let windows = candle_windows(&start, &end, &Granularity::OneDay).unwrap();
+        assert_eq!(windows, vec![(start, end)]);
+
+        assert!(candle_windows(&start, &end, &Granularity::UnknownGranularity).is_err());
+    }
+
+    fn ohlcv_candle(start: i64, open: i32, high: i32, low: i32, close: i32, volume: i32) -> Candle {
+        Candle {
+            start: start.to_string(),
+            low: BigDecimal::from(low),
+            high: BigDecimal::from(high),
+            open: BigDecimal::from(open),
+            close: BigDecimal::from(close),
+            volume: BigDecimal::from(volume),
+        }
+    }
+
+    #[test]
+    fn test_resample() {
+        // Two FIVE_MINUTE buckets, newest first as returned by Coinbase.
+        let mut candles: Vec<Candle> = (0..10)
+            .map(|i| {
+                ohlcv_candle(
+                    600 + i * 60,
+                    10 + i as i32,
+                    20 + i as i32,
+                    5 - i as i32,
+                    11 + i as i32,
+                    1,
+                )
+            })
+            .collect();
+        candles.reverse();
+
+        let resampled =
+            resample(&candles, &Granularity::OneMinute, &Granularity::FiveMinute).unwrap();
+        assert_eq!(resampled.len(), 2);
+
+        assert_eq!(resampled[0].start, "600");
+        assert_eq!(resampled[0].open, BigDecimal::from(10));
+        assert_eq!(resampled[0].high, BigDecimal::from(24));
+        assert_eq!(resampled[0].low, BigDecimal::from(1));
+        assert_eq!(resampled[0].close, BigDecimal::from(15));
+        assert_eq!(resampled[0].volume, BigDecimal::from(5));
+
+        assert_eq!(resampled[1].start, "900");
+        assert_eq!(resampled[1].open, BigDecimal::from(15));
+        assert_eq!(resampled[1].high, BigDecimal::from(29));
+        assert_eq!(resampled[1].low, BigDecimal::from(-4));
+        assert_eq!(resampled[1].close, BigDecimal::from(20));
+        assert_eq!(resampled[1].volume, BigDecimal::from(5));
+    }
+
+    #[test]
+    fn test_sort_candles_chronological() {
+        let mut candles = vec![candle_at(120), candle_at(0), candle_at(180), candle_at(60)];
+        sort_candles_chronological(&mut candles);
+        let starts: Vec<&str> = candles.iter().map(|candle| candle.start.as_str()).collect();
+        assert_eq!(starts, vec!["0", "60", "120", "180"]);
+        assert!(validate_contiguous(&candles, &Granularity::OneMinute).is_ok());
+    }
+
+    #[test]
+    fn test_validate_contiguous() {
+        let candles = vec![candle_at(0), candle_at(60), candle_at(180)];
+        assert!(validate_contiguous(&candles, &Granularity::OneMinute).is_err());
+
+        let candles = vec![candle_at(60), candle_at(0)];
+        assert!(validate_contiguous(&candles, &Granularity::OneMinute).is_err());
+
+        let candles = vec![candle_at(0), candle_at(300)];
+        assert!(validate_contiguous(&candles, &Granularity::OneMinute).is_err());
+        assert!(validate_contiguous(&candles, &Granularity::FiveMinute).is_ok());
+        assert!(validate_contiguous(&candles, &Granularity::UnknownGranularity).is_err());
+
+        assert!(validate_contiguous(&[], &Granularity::OneMinute).is_ok());
+    }
+
+    #[test]
+    fn test_resample_invalid_granularities() {
+        let candles = vec![candle_at(0)];
+        assert!(resample(&candles, &Granularity::OneHour, &Granularity::FiveMinute).is_err());
+        assert!(resample(
+            &candles,
+            &Granularity::UnknownGranularity,
+            &Granularity::OneHour
+        )
+        .is_err());
+        assert!(resample(&candles, &Granularity::OneHour, &Granularity::OneHour).is_ok());
+    }
+
+    #[test]
+    fn test_merge_candles() {
+        let candles = vec![
+            candle_at(180),
+            candle_at(120),
+            candle_at(120),
+            candle_at(60),
+            candle_at(0),
+        ];
+        let starts: Vec<String> = merge_candles(candles)
+            .into_iter()
+            .map(|candle| candle.start)
+            .collect();
+        assert_eq!(starts, vec!["0", "60", "120", "180"]);
+    }
+
+    #[test]
+    fn test_granularity_seconds_round_trip() {
+        let granularities = [
+            Granularity::OneMinute,
+            Granularity::FiveMinute,
+            Granularity::FifteenMinute,
+            Granularity::ThirtyMinute,
+            Granularity::OneHour,
+            Granularity::TwoHour,
+            Granularity::SixHour,
+            Granularity::OneDay,
+        ];
+        for granularity in granularities {
+            let seconds = granularity.to_seconds().unwrap();
+            assert_eq!(Granularity::from_seconds(seconds), Some(granularity));
+        }
+        assert_eq!(Granularity::OneHour.to_seconds(), Some(3600));
+        assert_eq!(Granularity::UnknownGranularity.to_seconds(), None);
+        assert_eq!(Granularity::from_seconds(120), None);
+    }
+
+    #[test]
+    fn test_candle_start_time() {
+        let candle = candle_at(1639508050);
+        assert_eq!(
+            candle.start_time().unwrap(),
+            DateTime::from_str("2021-12-14T18:54:10Z").unwrap()
+        );
+
+        let mut candle = candle_at(0);
+        candle.start = "not a timestamp".to_string();
+        assert!(candle.start_time().is_err());
+    }
 }
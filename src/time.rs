@@ -0,0 +1,54 @@
+//! Structures for Coinbase's server time
+
+use serde::{Deserialize, Deserializer};
+
+use crate::DateTime;
+
+/// Structure representing Coinbase's current server time
+#[derive(Deserialize, Debug)]
+pub struct ServerTime {
+    /// Current time in ISO 8601 format.
+    pub iso: DateTime,
+    /// Seconds elapsed since the Unix epoch.
+    #[serde(
+        rename = "epochSeconds",
+        deserialize_with = "deserialize_i64_from_string"
+    )]
+    pub epoch_seconds: i64,
+    /// Milliseconds elapsed since the Unix epoch.
+    #[serde(
+        rename = "epochMillis",
+        deserialize_with = "deserialize_i64_from_string"
+    )]
+    pub epoch_millis: i64,
+}
+
+// Coinbase sends the epoch values as json strings.
+fn deserialize_i64_from_string<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    value.parse().map_err(serde::de::Error::custom)
+}
+
+//=========== TESTS ===========================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_time_deserialize() {
+        let input = r##"{
+            "iso": "2023-08-26T12:34:56.789Z",
+            "epochSeconds": "1693053296",
+            "epochMillis": "1693053296789"
+        }"##;
+        let result: ServerTime = serde_json::from_slice(input.as_bytes()).unwrap();
+        assert_eq!(result.iso.timestamp(), 1693053296);
+        assert_eq!(result.epoch_seconds, 1693053296);
+        assert_eq!(result.epoch_millis, 1693053296789);
+        assert_eq!(result.iso.timestamp_millis(), result.epoch_millis);
+    }
+}
@@ -1,7 +1,16 @@
 //! Utility functions
 
+use anyhow::anyhow;
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, TimeZone, Utc};
 use dotenvy::dotenv;
+use futures::{pin_mut, Stream, StreamExt};
+use serde::{Deserialize, Deserializer};
 use std::env;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::DateTime;
 
 /// Get client_id, client_secret and redirect_url from environment variables
 ///
@@ -21,3 +30,170 @@ pub fn get_env_variables() -> (String, String, String) {
 
     (client_id, client_secret, redirect_url)
 }
+
+/// Midnight UTC of the given day, e.g. to set candles' `start` and `end`.
+///
+/// ```
+/// # use coinbase_v3::utils;
+/// let start = utils::datetime_from_ymd(2023, 1, 12).unwrap();
+/// assert_eq!(start.to_rfc3339(), "2023-01-12T00:00:00+00:00");
+/// ```
+///
+/// Returns an error if the date does not exist.
+pub fn datetime_from_ymd(year: i32, month: u32, day: u32) -> anyhow::Result<DateTime> {
+    let date = NaiveDate::from_ymd_opt(year, month, day)
+        .ok_or_else(|| anyhow!("Invalid date {}-{}-{}", year, month, day))?;
+    Ok(Utc.from_utc_datetime(&date.and_time(chrono::NaiveTime::MIN)))
+}
+
+/// Parse an RFC 3339 date, e.g. `2023-01-12T10:30:00Z`, converted to UTC if it has an offset.
+///
+/// ```
+/// # use coinbase_v3::utils;
+/// let end_time = utils::parse_rfc3339("2023-01-12T12:30:00+02:00").unwrap();
+/// assert_eq!(end_time.to_rfc3339(), "2023-01-12T10:30:00+00:00");
+/// ```
+pub fn parse_rfc3339(s: &str) -> anyhow::Result<DateTime> {
+    let datetime = chrono::DateTime::parse_from_rfc3339(s)
+        .map_err(|err| anyhow!("Invalid RFC 3339 date {:?}: {}", s, err))?;
+    Ok(datetime.with_timezone(&Utc))
+}
+
+// Accounting for the fact that when no data are available Coinbase return sometimes null sometimes
+// the empty string ""
+pub(crate) fn deserialize_optional_bigdecimal<'de, D>(
+    deserializer: D,
+) -> Result<Option<BigDecimal>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.and_then(|b| BigDecimal::from_str(&b).ok()))
+}
+
+/// Drain a paginated stream, as returned by e.g. `list_orders` or `list_fills`, into a single `Vec`.
+///
+/// When `delay` is given, wait that long before requesting each page after the first one, to stay
+/// under Coinbase's rate limits. Stops at the first error.
+///
+/// ```no_run
+/// # use coinbase_v3::{client, utils};
+/// # use std::time::Duration;
+/// # async fn run(cb_client: client::CbClient<'_>) {
+/// let fills_stream = cb_client.list_fills(None, None, None, None, None, None);
+/// let fills = utils::collect_all(fills_stream, Some(Duration::from_millis(100)))
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+pub async fn collect_all<T, E, S>(stream: S, delay: Option<Duration>) -> Result<Vec<T>, E>
+where
+    S: Stream<Item = Result<Vec<T>, E>>,
+{
+    pin_mut!(stream);
+    let mut items = Vec::new();
+    let mut first_page = true;
+    loop {
+        if let (Some(delay), false) = (delay, first_page) {
+            tokio::time::sleep(delay).await;
+        }
+        first_page = false;
+        match stream.next().await {
+            Some(page) => items.extend(page?),
+            None => return Ok(items),
+        }
+    }
+}
+
+/// Assert that every `(variant, wire)` pair deserializes from, serializes to and displays as
+/// the exact Coinbase string `wire`.
+#[cfg(test)]
+pub(crate) fn assert_enum_round_trip<T>(cases: &[(T, &str)])
+where
+    T: serde::Serialize
+        + serde::de::DeserializeOwned
+        + std::fmt::Display
+        + std::fmt::Debug
+        + PartialEq,
+{
+    for (variant, wire) in cases {
+        let quoted = format!("\"{}\"", wire);
+        assert_eq!(&serde_json::from_str::<T>(&quoted).unwrap(), variant);
+        assert_eq!(serde_json::to_string(variant).unwrap(), quoted);
+        assert_eq!(&variant.to_string(), wire);
+    }
+}
+
+//=========== TESTS ===========================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Decimal {
+        #[serde(default, deserialize_with = "deserialize_optional_bigdecimal")]
+        value: Option<BigDecimal>,
+    }
+
+    #[test]
+    fn test_datetime_from_ymd() {
+        let datetime = datetime_from_ymd(2024, 2, 29).unwrap();
+        assert_eq!(
+            datetime,
+            DateTime::from_str("2024-02-29T00:00:00Z").unwrap()
+        );
+
+        assert!(datetime_from_ymd(2023, 2, 29).is_err());
+        assert!(datetime_from_ymd(2023, 13, 1).is_err());
+        assert!(datetime_from_ymd(2023, 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_rfc3339() {
+        let expected = DateTime::from_str("2023-01-12T10:30:00Z").unwrap();
+        assert_eq!(parse_rfc3339("2023-01-12T10:30:00Z").unwrap(), expected);
+        assert_eq!(
+            parse_rfc3339("2023-01-12T12:30:00+02:00").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_rfc3339("2023-01-12T10:30:00.5Z").unwrap(),
+            expected + chrono::Duration::milliseconds(500)
+        );
+
+        assert!(parse_rfc3339("2023-01-12").is_err());
+        assert!(parse_rfc3339("2023-01-12 10:30:00").is_err());
+        assert!(parse_rfc3339("not a date").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_collect_all() {
+        let pages: Vec<Result<Vec<i32>, String>> = vec![Ok(vec![1, 2]), Ok(vec![]), Ok(vec![3])];
+        let items = collect_all(futures::stream::iter(pages), Some(Duration::from_millis(1)))
+            .await
+            .unwrap();
+        assert_eq!(items, vec![1, 2, 3]);
+
+        let pages: Vec<Result<Vec<i32>, String>> =
+            vec![Ok(vec![1]), Err("rate limited".to_string()), Ok(vec![2])];
+        let result = collect_all(futures::stream::iter(pages), None).await;
+        assert_eq!(result, Err("rate limited".to_string()));
+    }
+
+    #[test]
+    fn test_deserialize_optional_bigdecimal() {
+        let result: Decimal = serde_json::from_str(r##"{"value": "12.5"}"##).unwrap();
+        assert_eq!(result.value, Some(BigDecimal::from_str("12.5").unwrap()));
+
+        let result: Decimal = serde_json::from_str(r##"{"value": ""}"##).unwrap();
+        assert_eq!(result.value, None);
+
+        let result: Decimal = serde_json::from_str(r##"{"value": null}"##).unwrap();
+        assert_eq!(result.value, None);
+
+        let result: Decimal = serde_json::from_str(r##"{}"##).unwrap();
+        assert_eq!(result.value, None);
+    }
+}
@@ -0,0 +1,801 @@
+//! WebSocket client for Coinbase Advanced Trade market data feeds
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use async_stream::try_stream;
+use bigdecimal::BigDecimal;
+use futures::stream::Stream;
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_enum_str::{Deserialize_enum_str, Serialize_enum_str};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::basic_oauth::AccessTokenProvider;
+use crate::client::backoff_delay;
+use crate::error::CbError;
+use crate::orders::{OrderSide, OrderType, Status};
+use crate::products::{Ask, Bid};
+use crate::DateTime;
+
+/// URL of Coinbase's Advanced Trade WebSocket feed.
+pub const WS_URL: &str = "wss://advanced-trade-ws.coinbase.com";
+
+/// Delay before reconnecting after the server closed the connection, doubled after each failed
+/// reconnection.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+/// Consecutive failed (re)connections after which the stream ends.
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+/// Time without any message after which the connection is considered dead. Coinbase sends a
+/// heartbeat every second.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Result<T> = std::result::Result<T, CbError>;
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Enum representing the type of a WebSocket event
+#[derive(Deserialize_enum_str, Serialize_enum_str, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventType {
+    /// First event after subscribing, with the current state.
+    Snapshot,
+    /// Subsequent changes.
+    Update,
+}
+
+/// Structure representing a ticker, as sent on the `ticker` channel
+#[derive(Deserialize, Debug)]
+pub struct Ticker {
+    /// The trading pair.
+    pub product_id: String,
+    /// Current price for the product.
+    pub price: BigDecimal,
+    /// 24h volume for the product.
+    pub volume_24_h: BigDecimal,
+    /// 24h lowest price.
+    pub low_24_h: BigDecimal,
+    /// 24h highest price.
+    pub high_24_h: BigDecimal,
+    /// 52w lowest price.
+    pub low_52_w: BigDecimal,
+    /// 52w highest price.
+    pub high_52_w: BigDecimal,
+    /// 24h price percentage change.
+    pub price_percent_chg_24_h: BigDecimal,
+}
+
+/// Structure representing an event of the `ticker` channel
+#[derive(Deserialize, Debug)]
+pub struct TickerEvent {
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    pub tickers: Vec<Ticker>,
+}
+
+/// Enum representing the side of a `level2` update
+#[derive(Deserialize_enum_str, Serialize_enum_str, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Level2Side {
+    Bid,
+    Offer,
+}
+
+/// Structure representing a price level update, as sent on the `level2` channel
+#[derive(Deserialize, Debug)]
+pub struct Level2Update {
+    pub side: Level2Side,
+    pub event_time: DateTime,
+    pub price_level: BigDecimal,
+    /// New size at this price level. Zero when the level is removed.
+    pub new_quantity: BigDecimal,
+}
+
+/// Structure representing an event of the `level2` channel
+#[derive(Deserialize, Debug)]
+pub struct Level2Event {
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    pub product_id: String,
+    pub updates: Vec<Level2Update>,
+}
+
+/// Local order book of a single product, maintained from `level2` events.
+///
+/// ```no_run
+/// # use coinbase_v3::ws;
+/// use futures::{pin_mut, stream::StreamExt};
+/// # tokio_test::block_on(async {
+/// let ws_client = ws::WsClient::new();
+/// let mut book = ws::Level2Book::new("BTC-USD");
+/// let level2_stream = ws_client.level2(&["BTC-USD"]);
+/// pin_mut!(level2_stream);
+/// while let Some(level2_event) = level2_stream.next().await {
+///     book.apply(&level2_event.unwrap());
+///     println!("spread: {:?}", book.spread());
+/// }
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct Level2Book {
+    product_id: String,
+    // Price levels to sizes, sorted by ascending price.
+    bids: BTreeMap<BigDecimal, BigDecimal>,
+    asks: BTreeMap<BigDecimal, BigDecimal>,
+}
+
+impl Level2Book {
+    /// Instantiate an empty book for `product_id`.
+    pub fn new(product_id: &str) -> Self {
+        Level2Book {
+            product_id: product_id.to_string(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+        }
+    }
+
+    /// Apply a `level2` event to the book.
+    ///
+    /// A snapshot replaces the whole book. Updates with a zero size remove their price level.
+    /// Events for other products are ignored.
+    pub fn apply(&mut self, event: &Level2Event) {
+        if event.product_id != self.product_id {
+            return;
+        }
+        if event.event_type == EventType::Snapshot {
+            self.bids.clear();
+            self.asks.clear();
+        }
+        for update in &event.updates {
+            let levels = match update.side {
+                Level2Side::Bid => &mut self.bids,
+                Level2Side::Offer => &mut self.asks,
+            };
+            if update.new_quantity == BigDecimal::from(0) {
+                levels.remove(&update.price_level);
+            } else {
+                levels.insert(update.price_level.clone(), update.new_quantity.clone());
+            }
+        }
+    }
+
+    /// Highest bid, if any.
+    pub fn best_bid(&self) -> Option<Bid> {
+        self.bids.iter().next_back().map(|(price, size)| Bid {
+            price: price.clone(),
+            size: size.clone(),
+        })
+    }
+
+    /// Lowest ask, if any.
+    pub fn best_ask(&self) -> Option<Ask> {
+        self.asks.iter().next().map(|(price, size)| Ask {
+            price: price.clone(),
+            size: size.clone(),
+        })
+    }
+
+    /// Difference between the best ask and the best bid, if both sides are set.
+    pub fn spread(&self) -> Option<BigDecimal> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+}
+
+/// Structure representing an order update, as sent on the `user` channel
+#[derive(Deserialize, Debug)]
+pub struct UserOrder {
+    pub order_id: String,
+    pub client_order_id: String,
+    /// Amount filled so far.
+    pub cumulative_quantity: BigDecimal,
+    /// Amount remaining to be filled.
+    pub leaves_quantity: BigDecimal,
+    /// Average filled price so far.
+    pub avg_price: BigDecimal,
+    pub total_fees: BigDecimal,
+    pub status: Status,
+    pub product_id: String,
+    pub creation_time: DateTime,
+    pub order_side: OrderSide,
+    #[serde(deserialize_with = "deserialize_order_type")]
+    pub order_type: OrderType,
+}
+
+/// Structure representing an event of the `user` channel
+#[derive(Deserialize, Debug)]
+pub struct UserEvent {
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    pub orders: Vec<UserOrder>,
+}
+
+/// The `user` channel sends order types in camel case (e.g. `StopLimit`) instead of the
+/// `STOP_LIMIT` form used by the REST API.
+fn deserialize_order_type<'de, D>(deserializer: D) -> std::result::Result<OrderType, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    let mut screaming_snake_case = String::new();
+    let mut previous_is_lowercase = false;
+    for c in value.chars() {
+        if c.is_uppercase() && previous_is_lowercase {
+            screaming_snake_case.push('_');
+        }
+        previous_is_lowercase = c.is_lowercase();
+        screaming_snake_case.push(c.to_ascii_uppercase());
+    }
+    screaming_snake_case
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+/// Envelope shared by all messages sent by the server.
+#[derive(Deserialize, Debug)]
+struct WsMessage {
+    #[serde(default)]
+    channel: String,
+    #[serde(default)]
+    events: serde_json::Value,
+    // Only set for error messages.
+    #[serde(default, rename = "type")]
+    message_type: String,
+    #[serde(default)]
+    message: String,
+}
+
+/// Client subscribing to Coinbase's WebSocket channels.
+///
+/// ```no_run
+/// # use coinbase_v3::ws;
+/// use futures::{pin_mut, stream::StreamExt};
+/// # tokio_test::block_on(async {
+/// let ws_client = ws::WsClient::new();
+/// let ticker_stream = ws_client.ticker(&["BTC-USD", "ETH-USD"]);
+/// pin_mut!(ticker_stream);
+/// while let Some(ticker_event) = ticker_stream.next().await {
+///     println!("{:#?}", ticker_event.unwrap());
+/// }
+/// # });
+/// ```
+pub struct WsClient<'a> {
+    url: String,
+    // Only required by the `user` channel.
+    access_token_provider: Option<&'a (dyn AccessTokenProvider + 'a)>,
+    // Reconnect when no message, not even a heartbeat, is received for that long.
+    heartbeat_timeout: Duration,
+    reconnect_delay: Duration,
+}
+
+impl<'a> WsClient<'a> {
+    /// Instantiate a new, unauthenticated, WebSocket client.
+    pub fn new() -> Self {
+        WsClient {
+            url: WS_URL.to_string(),
+            access_token_provider: None,
+            heartbeat_timeout: HEARTBEAT_TIMEOUT,
+            reconnect_delay: RECONNECT_DELAY,
+        }
+    }
+
+    /// Reconnect when no message is received for `heartbeat_timeout`, 10 seconds by default.
+    ///
+    /// Coinbase sends a heartbeat every second, so a silent connection is most likely half-open.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use coinbase_v3::ws;
+    /// let ws_client = ws::WsClient::new().with_heartbeat_timeout(Duration::from_secs(5));
+    /// ```
+    pub fn with_heartbeat_timeout(mut self, heartbeat_timeout: Duration) -> Self {
+        self.heartbeat_timeout = heartbeat_timeout;
+        self
+    }
+
+    /// Authenticate subscriptions with the given provider, as required by the `user` channel.
+    ///
+    /// Coinbase's WebSocket feed expects JWTs, as minted by
+    /// [`JwtTokenProvider`](`crate::jwt_auth::JwtTokenProvider`).
+    ///
+    /// ```no_run
+    /// # use coinbase_v3::{jwt_auth, ws};
+    /// # let jwt_provider = jwt_auth::JwtTokenProvider::new("", "").unwrap();
+    /// let ws_client = ws::WsClient::new().with_access_token_provider(&jwt_provider);
+    /// ```
+    pub fn with_access_token_provider(
+        mut self,
+        access_token_provider: &'a (dyn AccessTokenProvider + 'a),
+    ) -> Self {
+        self.access_token_provider = Some(access_token_provider);
+        self
+    }
+
+    /// Subscribe to the `ticker` channel for the given products.
+    ///
+    /// The connection is kept alive by also subscribing to the `heartbeats` channel, and
+    /// re-established if the server closes it or if no message, not even a heartbeat, is
+    /// received for a while, see [with_heartbeat_timeout()](`crate::ws::WsClient::with_heartbeat_timeout`).
+    /// Failed reconnections are retried with exponential backoff: the stream ends after 5
+    /// consecutive failures. Messages which can not be parsed are skipped.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/docs/ws-channels#ticker-channel)
+    pub fn ticker<'b>(
+        &'b self,
+        product_ids: &[&str],
+    ) -> impl Stream<Item = Result<TickerEvent>> + 'b {
+        self.subscribe("ticker", "ticker", product_ids)
+    }
+
+    /// Subscribe to the `level2` channel for the given products, to be applied to a
+    /// [`Level2Book`].
+    ///
+    /// As for [ticker()](`crate::ws::WsClient::ticker`), the connection is re-established if
+    /// the server closes it, starting again with a snapshot.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/docs/ws-channels#level2-channel)
+    pub fn level2<'b>(
+        &'b self,
+        product_ids: &[&str],
+    ) -> impl Stream<Item = Result<Level2Event>> + 'b {
+        // Level2 messages are sent on the `l2_data` channel.
+        self.subscribe("level2", "l2_data", product_ids)
+    }
+
+    /// Subscribe to the `user` channel, receiving updates of the user's orders for the given
+    /// products, or for all products if `product_ids` is empty.
+    ///
+    /// Requires an access token provider, see
+    /// [with_access_token_provider()](`crate::ws::WsClient::with_access_token_provider`), whose
+    /// key has the `view` permission (`wallet:orders:read` scope). A rejected authentication ends
+    /// the stream with a [`CbError::Auth`] error instead of reconnecting.
+    ///
+    /// [Coinbase API reference](https://docs.cloud.coinbase.com/advanced-trade-api/docs/ws-channels#user-channel)
+    pub fn user<'b>(&'b self, product_ids: &[&str]) -> impl Stream<Item = Result<UserEvent>> + 'b {
+        self.subscribe("user", "user", product_ids)
+    }
+
+    /// Subscribe to `channel` for the given products, yielding the parsed events received on
+    /// `events_channel`.
+    fn subscribe<'b, T>(
+        &'b self,
+        channel: &'static str,
+        events_channel: &'static str,
+        product_ids: &[&str],
+    ) -> impl Stream<Item = Result<T>> + 'b
+    where
+        T: serde::de::DeserializeOwned + 'b,
+    {
+        let product_ids: Vec<String> = product_ids.iter().map(|id| id.to_string()).collect();
+        try_stream! {
+            if channel == "user" && self.access_token_provider.is_none() {
+                Err(CbError::Auth(
+                    "the user channel requires an access token provider".to_string(),
+                ))?;
+            }
+            let mut failed_attempts = 0;
+            loop {
+                let mut socket = match self.connect(channel, &product_ids).await {
+                    Ok(socket) => {
+                        failed_attempts = 0;
+                        socket
+                    }
+                    Err(err @ CbError::Auth(_)) => Err(err)?,
+                    Err(err) => {
+                        failed_attempts += 1;
+                        if failed_attempts >= MAX_RECONNECT_ATTEMPTS {
+                            Err(err)?;
+                        }
+                        tokio::time::sleep(backoff_delay(self.reconnect_delay, failed_attempts))
+                            .await;
+                        continue;
+                    }
+                };
+                // Without any message, not even a heartbeat, the connection is likely half-open.
+                while let Ok(Some(message)) =
+                    tokio::time::timeout(self.heartbeat_timeout, socket.next()).await
+                {
+                    match message {
+                        Ok(Message::Text(text)) => match parse_events::<T>(events_channel, &text) {
+                            Ok(events) => {
+                                for event in events {
+                                    yield event;
+                                }
+                            }
+                            // A single malformed message should not end the subscription.
+                            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+                            Err(CbError::Serde { source, .. }) => {
+                                #[cfg(feature = "tracing")]
+                                tracing::warn!(error = %source, "Skipping unparseable WebSocket message");
+                            }
+                            Err(err) => Err(err)?,
+                        },
+                        Ok(Message::Close(Some(frame))) if frame.code == CloseCode::Policy => {
+                            Err(CbError::Auth(frame.reason.to_string()))?;
+                        }
+                        Ok(Message::Close(_)) | Err(_) => break,
+                        // Pings are answered by tungstenite itself.
+                        Ok(_) => {}
+                    }
+                }
+                tokio::time::sleep(self.reconnect_delay).await;
+            }
+        }
+    }
+
+    /// Open a connection and subscribe to `channel` and to the `heartbeats` channel.
+    async fn connect(&self, channel: &str, product_ids: &[String]) -> Result<Socket> {
+        let (mut socket, _) = tokio_tungstenite::connect_async(self.url.as_str()).await?;
+        for mut message in [
+            subscribe_message(channel, product_ids),
+            subscribe_message("heartbeats", &[]),
+        ] {
+            if let Some(access_token_provider) = self.access_token_provider {
+                // JWTs are short-lived: a new one is minted for each subscription.
+                message["jwt"] = serde_json::Value::String(
+                    access_token_provider.access_token()?.secret().clone(),
+                );
+            }
+            socket.send(Message::Text(message.to_string())).await?;
+        }
+        Ok(socket)
+    }
+}
+
+impl Default for WsClient<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn subscribe_message(channel: &str, product_ids: &[String]) -> serde_json::Value {
+    serde_json::json!({
+        "type": "subscribe",
+        "channel": channel,
+        "product_ids": product_ids,
+    })
+}
+
+/// Parse the events of a message received on `channel`.
+///
+/// Messages from other channels (heartbeats, subscriptions) are skipped. Error messages sent by
+/// the server are returned as errors.
+fn parse_events<T>(channel: &str, text: &str) -> Result<Vec<T>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let message: WsMessage = serde_json::from_str(text)?;
+    if message.message_type == "error" {
+        if message.message.to_lowercase().contains("authentication") {
+            return Err(CbError::Auth(message.message));
+        }
+        return Err(CbError::WebSocket(message.message));
+    }
+    if message.channel != channel {
+        return Ok(Vec::new());
+    }
+    Ok(serde_json::from_value(message.events)?)
+}
+
+//=========== TESTS ===========================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use tokio::net::TcpListener;
+
+    const TICKER_MESSAGE: &str = r##"{
+        "channel": "ticker",
+        "client_id": "",
+        "timestamp": "2023-02-09T20:30:37.167359596Z",
+        "sequence_num": 0,
+        "events": [
+            {
+                "type": "snapshot",
+                "tickers": [
+                    {
+                        "type": "ticker",
+                        "product_id": "BTC-USD",
+                        "price": "21932.98",
+                        "volume_24_h": "16038.28770938",
+                        "low_24_h": "21835.29",
+                        "high_24_h": "23011.18",
+                        "low_52_w": "15460",
+                        "high_52_w": "48240",
+                        "price_percent_chg_24_h": "-4.15775596190603"
+                    }
+                ]
+            }
+        ]
+    }"##;
+
+    #[test]
+    fn test_parse_ticker_events() {
+        let events = parse_events::<TickerEvent>("ticker", TICKER_MESSAGE).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, EventType::Snapshot);
+        assert_eq!(events[0].tickers[0].product_id, "BTC-USD");
+        assert_eq!(
+            events[0].tickers[0].price,
+            BigDecimal::from_str("21932.98").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_heartbeats_skipped() {
+        let input = r##"{
+            "channel": "heartbeats",
+            "client_id": "",
+            "timestamp": "2023-06-23T20:31:56.121961769Z",
+            "sequence_num": 0,
+            "events": [
+                {
+                    "current_time": "2023-06-23 20:31:56.121961769 +0000 UTC m=+91717.525857105",
+                    "heartbeat_counter": "3049"
+                }
+            ]
+        }"##;
+        let events = parse_events::<TickerEvent>("ticker", input).unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_parse_error_message() {
+        let input = r##"{"type": "error", "message": "failure to subscribe"}"##;
+        let result = parse_events::<TickerEvent>("ticker", input);
+        assert!(
+            matches!(result, Err(CbError::WebSocket(message)) if message == "failure to subscribe")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ticker_stream() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let mut subscriptions = Vec::new();
+            for _ in 0..2 {
+                if let Some(Ok(Message::Text(text))) = socket.next().await {
+                    subscriptions.push(serde_json::from_str::<serde_json::Value>(&text).unwrap());
+                }
+            }
+            socket
+                .send(Message::Text(TICKER_MESSAGE.to_string()))
+                .await
+                .unwrap();
+            subscriptions
+        });
+
+        let ws_client = WsClient {
+            url,
+            ..WsClient::new()
+        };
+        let ticker_stream = ws_client.ticker(&["BTC-USD"]);
+        futures::pin_mut!(ticker_stream);
+        let event = ticker_stream.next().await.unwrap().unwrap();
+        assert_eq!(event.tickers[0].product_id, "BTC-USD");
+
+        let subscriptions = server.await.unwrap();
+        assert_eq!(subscriptions[0]["channel"], "ticker");
+        assert_eq!(subscriptions[0]["product_ids"][0], "BTC-USD");
+        assert_eq!(subscriptions[1]["channel"], "heartbeats");
+    }
+
+    /// Accept a connection and read its two subscription messages.
+    async fn accept_subscribed(listener: &TcpListener) -> Socket {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(MaybeTlsStream::Plain(stream))
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            socket.next().await.unwrap().unwrap();
+        }
+        socket
+    }
+
+    /// Client connecting to `listener`, reconnecting quickly.
+    fn test_client(listener: &TcpListener) -> WsClient<'static> {
+        WsClient {
+            url: format!("ws://{}", listener.local_addr().unwrap()),
+            heartbeat_timeout: Duration::from_millis(200),
+            reconnect_delay: Duration::from_millis(10),
+            ..WsClient::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ticker_stream_skips_unparseable_message() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_client = test_client(&listener);
+        let server = async {
+            let mut socket = accept_subscribed(&listener).await;
+            let invalid_message = TICKER_MESSAGE.replace(r#""price": "21932.98","#, "");
+            socket.send(Message::Text(invalid_message)).await.unwrap();
+            socket
+                .send(Message::Text(TICKER_MESSAGE.to_string()))
+                .await
+                .unwrap();
+            socket
+        };
+
+        let ticker_stream = ws_client.ticker(&["BTC-USD"]);
+        futures::pin_mut!(ticker_stream);
+        let (event, _socket) = tokio::join!(ticker_stream.next(), server);
+        let event = event.unwrap().unwrap();
+        assert_eq!(
+            event.tickers[0].price,
+            BigDecimal::from_str("21932.98").unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ticker_stream_reconnects_silent_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_client = test_client(&listener);
+        let server = async {
+            // Half-open connection: subscribed, but nothing is ever sent.
+            let silent_socket = accept_subscribed(&listener).await;
+            // Failed reconnection: the connection is dropped before the handshake.
+            drop(listener.accept().await.unwrap());
+            let mut socket = accept_subscribed(&listener).await;
+            socket
+                .send(Message::Text(TICKER_MESSAGE.to_string()))
+                .await
+                .unwrap();
+            (silent_socket, socket)
+        };
+
+        let ticker_stream = ws_client.ticker(&["BTC-USD"]);
+        futures::pin_mut!(ticker_stream);
+        let (event, _sockets) = tokio::join!(ticker_stream.next(), server);
+        assert_eq!(event.unwrap().unwrap().tickers[0].product_id, "BTC-USD");
+    }
+
+    #[tokio::test]
+    async fn test_ticker_stream_ends_after_failed_reconnections() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_client = WsClient {
+            reconnect_delay: Duration::from_millis(1),
+            ..test_client(&listener)
+        };
+        drop(listener);
+
+        let ticker_stream = ws_client.ticker(&["BTC-USD"]);
+        futures::pin_mut!(ticker_stream);
+        assert!(matches!(
+            ticker_stream.next().await,
+            Some(Err(CbError::WebSocket(_)))
+        ));
+        assert!(ticker_stream.next().await.is_none());
+    }
+
+    fn level2_event(event_type: &str, updates: &[(&str, &str, &str)]) -> Level2Event {
+        let updates: Vec<serde_json::Value> = updates
+            .iter()
+            .map(|(side, price_level, new_quantity)| {
+                serde_json::json!({
+                    "side": side,
+                    "event_time": "2023-02-09T20:32:50.714964855Z",
+                    "price_level": price_level,
+                    "new_quantity": new_quantity,
+                })
+            })
+            .collect();
+        let message = serde_json::json!({
+            "channel": "l2_data",
+            "client_id": "",
+            "timestamp": "2023-02-09T20:32:50.714964855Z",
+            "sequence_num": 0,
+            "events": [{"type": event_type, "product_id": "BTC-USD", "updates": updates}],
+        });
+        parse_events::<Level2Event>("l2_data", &message.to_string())
+            .unwrap()
+            .pop()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_level2_book() {
+        let mut book = Level2Book::new("BTC-USD");
+        assert!(book.best_bid().is_none());
+        assert!(book.spread().is_none());
+
+        book.apply(&level2_event(
+            "snapshot",
+            &[
+                ("bid", "100.5", "1"),
+                ("bid", "100", "2"),
+                ("offer", "101", "0.5"),
+                ("offer", "102", "3"),
+            ],
+        ));
+        assert_eq!(
+            book.best_bid().unwrap().price,
+            BigDecimal::from_str("100.5").unwrap()
+        );
+        assert_eq!(book.best_ask().unwrap().price, BigDecimal::from(101));
+        assert_eq!(book.spread().unwrap(), BigDecimal::from_str("0.5").unwrap());
+
+        book.apply(&level2_event(
+            "update",
+            &[
+                ("bid", "100.5", "0.00000000"),
+                ("offer", "101", "0"),
+                ("offer", "101.5", "4"),
+            ],
+        ));
+        assert_eq!(book.best_bid().unwrap().price, BigDecimal::from(100));
+        assert_eq!(book.best_bid().unwrap().size, BigDecimal::from(2));
+        assert_eq!(
+            book.best_ask().unwrap().price,
+            BigDecimal::from_str("101.5").unwrap()
+        );
+        assert_eq!(book.spread().unwrap(), BigDecimal::from_str("1.5").unwrap());
+
+        book.apply(&level2_event("snapshot", &[("bid", "99", "1")]));
+        assert_eq!(book.best_bid().unwrap().price, BigDecimal::from(99));
+        assert!(book.best_ask().is_none());
+    }
+
+    #[test]
+    fn test_parse_user_events() {
+        let input = r##"{
+            "channel": "user",
+            "client_id": "",
+            "timestamp": "2023-02-09T20:33:57.609931463Z",
+            "sequence_num": 0,
+            "events": [
+                {
+                    "type": "snapshot",
+                    "orders": [
+                        {
+                            "order_id": "0000-000000-000000",
+                            "client_order_id": "11111-000000-000000",
+                            "cumulative_quantity": "0",
+                            "leaves_quantity": "0.000994",
+                            "avg_price": "0",
+                            "total_fees": "0",
+                            "status": "OPEN",
+                            "product_id": "BTC-USD",
+                            "creation_time": "2022-12-07T19:42:18.719312Z",
+                            "order_side": "BUY",
+                            "order_type": "StopLimit"
+                        }
+                    ]
+                }
+            ]
+        }"##;
+        let events = parse_events::<UserEvent>("user", input).unwrap();
+        let order = &events[0].orders[0];
+        assert_eq!(order.status, Status::Open);
+        assert_eq!(order.order_side, OrderSide::Buy);
+        assert_eq!(order.order_type, OrderType::StopLimitOrderType);
+        assert_eq!(
+            order.leaves_quantity,
+            BigDecimal::from_str("0.000994").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_authentication_failure() {
+        let input = r##"{"type": "error", "message": "authentication failure"}"##;
+        let result = parse_events::<UserEvent>("user", input);
+        assert!(matches!(result, Err(CbError::Auth(_))));
+    }
+
+    #[tokio::test]
+    async fn test_user_requires_access_token_provider() {
+        let ws_client = WsClient::new();
+        let user_stream = ws_client.user(&[]);
+        futures::pin_mut!(user_stream);
+        assert!(matches!(
+            user_stream.next().await,
+            Some(Err(CbError::Auth(_)))
+        ));
+        assert!(user_stream.next().await.is_none());
+    }
+}